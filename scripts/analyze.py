@@ -40,7 +40,7 @@ class FunctionSmtTime:
 class Project:
     def __init__(self, json):
         self.name = json["runner"]["run_configuration"]["name"]
-        self.refspec = json["runner"]["run_configuration"]["refspec"]
+        self.refspec = json["runner"].get("refspec") or json["runner"]["run_configuration"]["refspec"]
         self.times_ms = json["times-ms"]
         if "verified" in json["verification-results"]:
             self.total_solved = json["verification-results"]["verified"]
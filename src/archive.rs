@@ -0,0 +1,22 @@
+use anyhow::anyhow;
+use flate2::{write::GzEncoder, Compression};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Pack `dir` into `<dir>.tar.gz` next to it, returning the archive's path
+pub fn archive_dir(dir: &Path) -> anyhow::Result<PathBuf> {
+    let name = dir
+        .file_name()
+        .ok_or_else(|| anyhow!("cannot archive {}: no directory name", dir.display()))?;
+    let mut archive_name = name.to_os_string();
+    archive_name.push(".tar.gz");
+    let archive_path = dir.with_file_name(archive_name);
+    let file = File::create(&archive_path)
+        .map_err(|e| anyhow!("cannot create {}: {}", archive_path.display(), e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    builder.append_dir_all(name, dir)?;
+    builder.into_inner()?.finish()?;
+    info!("Archived {} to {}", dir.display(), archive_path.display());
+    Ok(archive_path)
+}
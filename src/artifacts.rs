@@ -0,0 +1,71 @@
+use crate::fingerprint::Sha256;
+use anyhow::anyhow;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Copy the files under `base` matching any of `patterns` into `dest`, preserving their paths
+/// relative to `base`.  Returns the copied paths, relative to `dest`.
+pub fn collect_artifacts(
+    base: &Path,
+    patterns: &[String],
+    dest: &Path,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut collected = Vec::new();
+    for pattern in patterns.iter() {
+        let full_pattern = Path::new(&glob::Pattern::escape(&base.to_string_lossy())).join(pattern);
+        let mut matched = false;
+        for entry in glob::glob(&full_pattern.to_string_lossy())
+            .map_err(|e| anyhow!("invalid artifact pattern {}: {}", pattern, e))?
+        {
+            let path = entry?;
+            if !path.is_file() {
+                continue;
+            }
+            matched = true;
+            let relative = path.strip_prefix(base)?.to_path_buf();
+            let target = dest.join(&relative);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&path, &target)
+                .map_err(|e| anyhow!("cannot copy artifact {}: {}", path.display(), e))?;
+            info!("\tCollected artifact {}", relative.display());
+            collected.push(relative);
+        }
+        if !matched {
+            warn!(
+                "artifact pattern {} matched no files in {}",
+                pattern,
+                base.display()
+            );
+        }
+    }
+    Ok(collected)
+}
+
+/// A digest of the `.smt2` files under `dir`, ignoring SMT comments and blank lines so that
+/// only changes to the queries themselves show.  `None` if there are no such files
+pub fn smt_digest(dir: &Path) -> anyhow::Result<Option<String>> {
+    let pattern = Path::new(&glob::Pattern::escape(&dir.to_string_lossy())).join("**/*.smt2");
+    let mut paths = glob::glob(&pattern.to_string_lossy())?.collect::<Result<Vec<_>, _>>()?;
+    if paths.is_empty() {
+        return Ok(None);
+    }
+    paths.sort();
+    let mut hasher = Sha256::new();
+    for path in paths {
+        // NUL-terminated, as neither paths nor queries contain it
+        hasher.update(path.strip_prefix(dir)?.to_string_lossy().as_bytes());
+        hasher.update(b"\0");
+        for line in fs::read_to_string(&path)?.lines() {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with(';') {
+                hasher.update(line.as_bytes());
+                hasher.update(b"\n");
+            }
+        }
+        hasher.update(b"\0");
+    }
+    Ok(Some(hasher.finish_hex()))
+}
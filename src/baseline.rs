@@ -0,0 +1,153 @@
+use crate::ProjectStatus;
+use anyhow::anyhow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// What an earlier run recorded for one project
+struct BaselineProject {
+    /// Empty for projects that were not run
+    hash: Option<String>,
+    status: Option<ProjectStatus>,
+    /// Only for runs with `--capture-smt`
+    smt_hash: Option<String>,
+    /// Only for runs with `--query-counts` or `--profile-verus`
+    smt_function_count: Option<u64>,
+    /// See [`crate::output::VerusOutput::pass_ratio`]
+    pass_ratio: Option<f64>,
+    /// Absent from output of Verus versions before it was reported
+    estimated_cpu_time_ms: Option<u64>,
+}
+
+/// The commit each project was verified at in an earlier run, and the metrics it recorded,
+/// read from that run's `--summary-json` output
+pub struct Baseline {
+    projects: BTreeMap<String, BaselineProject>,
+    /// Across all projects
+    pass_ratio: Option<f64>,
+    estimated_cpu_time_ms: Option<u64>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("cannot read baseline {}: {}", path.display(), e))?;
+        let json: serde_json::Value = serde_json::from_str(&text)
+            .map_err(|e| anyhow!("cannot parse baseline {}: {}", path.display(), e))?;
+        let projects = json["projects"]
+            .as_array()
+            .ok_or_else(|| anyhow!("baseline {} has no projects list", path.display()))?;
+        let projects = projects
+            .iter()
+            .filter_map(|p| {
+                let project = BaselineProject {
+                    hash: p["hash"]
+                        .as_str()
+                        .filter(|hash| !hash.is_empty())
+                        .map(String::from),
+                    status: serde_json::from_value(p["status"].clone()).ok(),
+                    smt_hash: p["smt_hash"].as_str().map(String::from),
+                    smt_function_count: p["smt_function_count"].as_u64(),
+                    pass_ratio: p["pass_ratio"].as_f64(),
+                    estimated_cpu_time_ms: p["estimated_cpu_time_ms"].as_u64(),
+                };
+                Some((p["name"].as_str()?.to_string(), project))
+            })
+            .collect();
+        Ok(Baseline {
+            projects,
+            pass_ratio: json["pass_ratio"].as_f64(),
+            estimated_cpu_time_ms: json["estimated_cpu_time_ms"].as_u64(),
+        })
+    }
+
+    /// `unchanged`, or `old→new` (abbreviated) if the project has moved since the baseline;
+    /// `None` if the baseline has no commit for it
+    pub fn revision_change(&self, project: &str, hash: &str) -> Option<String> {
+        let old = self.projects.get(project)?.hash.as_ref()?;
+        let short = |h: &str| h.chars().take(7).collect::<String>();
+        Some(if old == hash {
+            "unchanged".to_string()
+        } else {
+            format!("{}→{}", short(old), short(hash))
+        })
+    }
+
+    /// Whether the project failed (or timed out) after passing in the baseline.  Allowed
+    /// failures and infrastructure errors say nothing new about the proofs, so don't count
+    pub fn newly_regressed(&self, project: &str, status: ProjectStatus) -> bool {
+        let passed = |status| {
+            matches!(
+                status,
+                Some(ProjectStatus::Success | ProjectStatus::PartialSuccess)
+            )
+        };
+        let failed = matches!(
+            status,
+            ProjectStatus::Failure | ProjectStatus::CompileFailed | ProjectStatus::TimedOut
+        );
+        failed && passed(self.projects.get(project).and_then(|p| p.status))
+    }
+
+    /// Whether the project's SMT queries differ from the baseline's; `None` if the baseline
+    /// has no SMT digest for it
+    pub fn smt_changed(&self, project: &str, smt_hash: &str) -> Option<bool> {
+        Some(self.projects.get(project)?.smt_hash.as_ref()? != smt_hash)
+    }
+
+    /// `old→new` if the number of functions sent to the solver differs from the baseline's,
+    /// a sign that the proof's structure changed before its timing does; `None` if it is the
+    /// same or the baseline has no count for the project
+    pub fn smt_function_count_change(&self, project: &str, count: u64) -> Option<String> {
+        let old = self.projects.get(project)?.smt_function_count?;
+        (old != count).then(|| format!("{}→{}", old, count))
+    }
+
+    /// `old→new`, as percentages, if the project's pass ratio differs from the baseline's;
+    /// `None` if it is the same or the baseline has no ratio for the project
+    pub fn pass_ratio_change(&self, project: &str, ratio: f64) -> Option<String> {
+        percentage_change(self.projects.get(project)?.pass_ratio?, ratio)
+    }
+
+    /// Like [`Self::pass_ratio_change`], for the whole suite
+    pub fn suite_pass_ratio_change(&self, ratio: f64) -> Option<String> {
+        percentage_change(self.pass_ratio?, ratio)
+    }
+
+    /// `old→new (±n%)`, in seconds, if the project's estimated CPU time differs from the
+    /// baseline's.  Unlike wall-clock time, this is comparable between machines with different
+    /// numbers of cores.  `None` if it is the same or the baseline has no time for the project
+    pub fn estimated_cpu_time_change(&self, project: &str, ms: u64) -> Option<String> {
+        seconds_change(self.projects.get(project)?.estimated_cpu_time_ms?, ms)
+    }
+
+    /// Like [`Self::estimated_cpu_time_change`], summed over the whole suite
+    pub fn total_estimated_cpu_time_change(&self, ms: u64) -> Option<String> {
+        seconds_change(self.estimated_cpu_time_ms?, ms)
+    }
+}
+
+/// Compared as displayed, so that rounding noise is not reported as a change
+fn percentage_change(old: f64, new: f64) -> Option<String> {
+    let (old, new) = (
+        format!("{:.1}%", old * 100.0),
+        format!("{:.1}%", new * 100.0),
+    );
+    (old != new).then(|| format!("{}→{}", old, new))
+}
+
+/// Compared to the tenth of a second, as displayed
+fn seconds_change(old_ms: u64, new_ms: u64) -> Option<String> {
+    let (old, new) = (
+        format!("{:.1}s", old_ms as f64 / 1000.0),
+        format!("{:.1}s", new_ms as f64 / 1000.0),
+    );
+    if old == new {
+        return None;
+    }
+    Some(if old_ms == 0 {
+        format!("{}→{}", old, new)
+    } else {
+        let percent = (new_ms as f64 - old_ms as f64) / old_ms as f64 * 100.0;
+        format!("{}→{} ({:+.0}%)", old, new, percent)
+    })
+}
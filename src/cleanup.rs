@@ -0,0 +1,64 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Make `path` writable by its owner, so that it can be deleted on platforms (e.g., Windows)
+/// that refuse to remove read-only files
+fn make_writable(path: &Path) -> io::Result<()> {
+    let mut permissions = fs::symlink_metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        permissions.set_mode(permissions.mode() | 0o200);
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions)
+}
+
+/// Like `fs::remove_dir_all`, but first clears read-only bits (e.g., on git's object files)
+pub fn remove_dir_all_resilient(path: &Path) -> io::Result<()> {
+    fn clear_read_only(path: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path)?;
+        if metadata.is_dir() {
+            make_writable(path)?;
+            for entry in fs::read_dir(path)? {
+                clear_read_only(&entry?.path())?;
+            }
+        } else if metadata.permissions().readonly() && !metadata.is_symlink() {
+            make_writable(path)?;
+        }
+        Ok(())
+    }
+    if let Err(e) = clear_read_only(path) {
+        warn!(
+            "cannot clear read-only bits under {}: {}",
+            path.display(),
+            e
+        );
+    }
+    fs::remove_dir_all(path)
+}
+
+/// A directory that is removed, with [`remove_dir_all_resilient`], when dropped
+pub struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    pub fn new(path: PathBuf) -> Self {
+        ScratchDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if let Err(e) = remove_dir_all_resilient(&self.0) {
+            warn!("failed to clean up {}: {}", self.0.display(), e);
+        }
+    }
+}
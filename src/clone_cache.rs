@@ -0,0 +1,70 @@
+use crate::cleanup;
+use crate::fingerprint::Sha256;
+use git2::Repository;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Branches and tags, mirrored as they are in the remote
+const MIRROR_REFSPECS: [&str; 2] = ["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"];
+
+/// The cache entry for `git_url`: the URL with everything but letters, digits, `-`, and `.`
+/// replaced, so that entries are readable and stable across runs, then a digest of the URL
+/// itself, so that URLs differing only in replaced characters don't share a mirror
+pub fn cache_key(git_url: &str) -> String {
+    let readable: String = git_url
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut hasher = Sha256::new();
+    hasher.update(git_url.as_bytes());
+    format!("{}-{}", readable, &hasher.finish_hex()[..16])
+}
+
+/// Bring the bare mirror of `git_url` under `cache_dir` up to date, creating it if needed,
+/// and return its path.  A mirror that cannot be opened or updated is removed and created
+/// afresh
+pub fn update(cache_dir: &Path, git_url: &str) -> anyhow::Result<PathBuf> {
+    let path = cache_dir.join(cache_key(git_url));
+    if path.exists() {
+        match Repository::open_bare(&path).and_then(|repo| fetch(&repo, git_url)) {
+            Ok(()) => return Ok(path),
+            Err(e) => {
+                warn!(
+                    "clone cache for {} is unusable ({}); repopulating it",
+                    git_url, e
+                );
+                cleanup::remove_dir_all_resilient(&path)?;
+            }
+        }
+    }
+    std::fs::create_dir_all(cache_dir)?;
+    let repo = Repository::init_bare(&path)?;
+    if let Err(e) = fetch(&repo, git_url) {
+        // Don't leave an empty mirror to be mistaken for a populated one
+        cleanup::remove_dir_all_resilient(&path)?;
+        return Err(e.into());
+    }
+    Ok(path)
+}
+
+/// Fetch the branches and tags that changed, pointing `HEAD` at the remote's default branch
+/// so that clones of the mirror check out the same branch as clones of the remote
+fn fetch(repo: &Repository, git_url: &str) -> Result<(), git2::Error> {
+    let mut remote = repo.remote_anonymous(git_url)?;
+    remote.connect(git2::Direction::Fetch)?;
+    let default_branch = remote
+        .default_branch()
+        .ok()
+        .and_then(|b| b.as_str().map(String::from));
+    remote.fetch(&MIRROR_REFSPECS, None, None)?;
+    if let Some(branch) = default_branch {
+        repo.set_head(&branch)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::fingerprint::Sha256;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A host path made available inside the container at the same path, so that commands
+/// need no path translation
+pub struct Mount {
+    pub path: PathBuf,
+    pub writable: bool,
+}
+
+/// Where a project's prepare script and Verus run when the configuration sets `container`
+pub struct Container {
+    /// `docker`, `podman`, or another program with a compatible `run`
+    pub runtime: String,
+    pub image: String,
+    pub mounts: Vec<Mount>,
+    /// `uid:gid` to run as, so that files created in the mounts belong to the host user (and
+    /// can be cleaned up and archived) rather than to root
+    pub user: Option<String>,
+}
+
+impl Container {
+    /// The name of `project`'s containers in this process: unique across concurrent runs,
+    /// so that [`Container::kill`] stops only this project's.  Characters the runtime
+    /// rejects are replaced, so a digest of the name as given keeps apart projects that
+    /// differ only in those (e.g., `a@z3` and `a_z3`)
+    pub fn name(project: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(project.as_bytes());
+        let digest = hasher.finish_hex();
+        let project: String = project
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("verita-{}-{}-{}", project, &digest[..8], std::process::id())
+    }
+
+    /// The command that runs `cmd` in a fresh container called `name`, with `cmd`'s working
+    /// directory and explicitly-set environment variables.  Nothing else from the host
+    /// environment is passed through
+    pub fn wrap(&self, cmd: &Command, name: &str) -> Command {
+        let mut wrapped = Command::new(&self.runtime);
+        wrapped.args(["run", "--rm", "--name", name]);
+        if let Some(user) = &self.user {
+            wrapped.arg("--user").arg(user);
+        }
+        for mount in self.mounts.iter() {
+            let path = mount.path.display();
+            wrapped.arg("-v").arg(if mount.writable {
+                format!("{}:{}", path, path)
+            } else {
+                format!("{}:{}:ro", path, path)
+            });
+        }
+        if let Some(dir) = cmd.get_current_dir() {
+            wrapped.arg("-w").arg(dir);
+        }
+        for (key, value) in cmd.get_envs() {
+            if let Some(value) = value {
+                let mut var = key.to_os_string();
+                var.push("=");
+                var.push(value);
+                wrapped.arg("-e").arg(var);
+            }
+        }
+        wrapped
+            .arg(&self.image)
+            .arg(cmd.get_program())
+            .args(cmd.get_args());
+        wrapped
+    }
+
+    /// Stop the container called `name`.  Killing the runtime's client (e.g., on a timeout)
+    /// leaves the container, and the processes in it, running
+    pub fn kill(&self, name: &str) {
+        let killed = Command::new(&self.runtime).args(["kill", name]).output();
+        if !killed.is_ok_and(|o| o.status.success()) {
+            tracing::warn!("cannot kill container {}", name);
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{preflight, Args};
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Something a run may trip over, but that does not stop it starting
+    Warn,
+    Fail,
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            CheckStatus::Pass => "pass",
+            CheckStatus::Warn => "warn",
+            CheckStatus::Fail => "FAIL",
+        })
+    }
+}
+
+/// The outcome of one of [`run_checks`]'s checks
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Check {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn from_result(name: impl Into<String>, result: anyhow::Result<String>) -> Self {
+        match result {
+            Ok(detail) => Check::new(name, CheckStatus::Pass, detail),
+            Err(e) => Check::new(name, CheckStatus::Fail, e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.status, self.name, self.detail)
+    }
+}
+
+/// Check everything a run with `args` relies on, without running anything: the Verus
+/// checkout and binary, the configuration, the solvers, the output directory, `sqlite3` (under
+/// `--sqlite`), access to the projects' repositories, and disk space.  Later checks are skipped when what they need
+/// failed
+pub fn run_checks(args: &Args) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let verus_repo = match std::fs::canonicalize(&args.verus_repo) {
+        Ok(repo) if repo.join("source").is_dir() => {
+            let detail = match git2::Repository::open(&repo) {
+                Ok(_) => repo.display().to_string(),
+                Err(_) => format!(
+                    "{} (not a git checkout, so no hash is recorded)",
+                    repo.display()
+                ),
+            };
+            checks.push(Check::new("verus repository", CheckStatus::Pass, detail));
+            Some(repo)
+        }
+        Ok(repo) => {
+            checks.push(Check::new(
+                "verus repository",
+                CheckStatus::Fail,
+                format!("{} has no source directory", repo.display()),
+            ));
+            None
+        }
+        Err(e) => {
+            checks.push(Check::new(
+                "verus repository",
+                CheckStatus::Fail,
+                format!("{}: {}", args.verus_repo.display(), e),
+            ));
+            None
+        }
+    };
+
+    if let Some(verus_repo) = &verus_repo {
+        checks.push(Check::from_result(
+            "verus binary",
+            crate::find_verus_binary(args, verus_repo).and_then(|(binary, _)| {
+                let output = std::process::Command::new(&binary)
+                    .arg("--version")
+                    .output()?;
+                if !output.status.success() {
+                    anyhow::bail!("{} --version failed", binary.display());
+                }
+                let version = String::from_utf8_lossy(&output.stdout);
+                Ok(format!(
+                    "{} ({})",
+                    binary.display(),
+                    version.lines().next().unwrap_or("").trim()
+                ))
+            }),
+        ));
+    }
+
+    let run_configuration = match crate::load_run_configuration(args) {
+        Ok(config) => {
+            checks.push(Check::new(
+                "configuration",
+                CheckStatus::Pass,
+                format!("{} project(s)", config.projects.len()),
+            ));
+            Some(config)
+        }
+        Err(e) => {
+            checks.push(Check::new(
+                "configuration",
+                CheckStatus::Fail,
+                e.to_string(),
+            ));
+            None
+        }
+    };
+
+    if let (Some(verus_repo), Some(config)) = (&verus_repo, &run_configuration) {
+        for probe in config.all_solver_probes() {
+            let expected = match probe.exe.as_str() {
+                "z3" => config.expected_z3_version.as_deref(),
+                "cvc5" => config.expected_cvc5_version.as_deref(),
+                _ => None,
+            };
+            checks.push(Check::from_result(
+                probe.exe.clone(),
+                crate::get_solver_version(verus_repo, &probe.exe, &probe.version_regex).and_then(
+                    |v| match expected {
+                        Some(expected) if !crate::solver_version_matches(expected, &v) => {
+                            anyhow::bail!("version {}, but {} is expected", v, expected)
+                        }
+                        _ => Ok(format!("version {}", v)),
+                    },
+                ),
+            ));
+        }
+    }
+
+    checks.push(Check::from_result(
+        "output directory",
+        writable(&args.output_dir).map(|()| args.output_dir.display().to_string()),
+    ));
+
+    if args.sqlite.is_some() {
+        checks.push(Check::from_result(
+            "sqlite3",
+            crate::output::SqliteSink::check_available().map(|v| format!("version {}", v)),
+        ));
+    }
+
+    if let Some(config) = &run_configuration {
+        for project in config.projects.iter() {
+            let refspec = config
+                .effective_refspec(project)
+                .expect("validated refspec");
+            let check = match preflight::check_refspec_reachable(&project.git_url, refspec) {
+                Ok(()) => Check::new(
+                    format!("access to {}", project.name),
+                    CheckStatus::Pass,
+                    project.git_url.clone(),
+                ),
+                // Usually missing credentials (or an SSH agent) for a private repository
+                Err(e) => Check::new(
+                    format!("access to {}", project.name),
+                    CheckStatus::Fail,
+                    e.to_string(),
+                ),
+            };
+            checks.push(check);
+        }
+
+        let required_gb = config
+            .projects
+            .iter()
+            .map(|p| {
+                p.estimated_clone_gb
+                    .unwrap_or(preflight::DEFAULT_ESTIMATED_CLONE_GB)
+            })
+            .sum();
+        let check = match preflight::check_disk_space(&std::env::temp_dir(), required_gb) {
+            Ok(()) => Check::new(
+                "disk space",
+                CheckStatus::Pass,
+                format!("at least {} GB available", required_gb),
+            ),
+            // The estimates are rough, so this doesn't rule out a run
+            Err(e) => Check::new("disk space", CheckStatus::Warn, e.to_string()),
+        };
+        checks.push(check);
+    }
+
+    checks
+}
+
+/// Whether files can be created in `dir`, creating it if needed
+fn writable(dir: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".verita-doctor");
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Verus arguments that only change how results are reported, so are left out of the
+/// fingerprint's `verus_args`
+pub const PRESENTATION_ARGS: [&str; 2] = ["--time-expanded", "--no-report-long-running"];
+
+/// Everything that can affect a project's verification result, and nothing else (e.g., its
+/// tags, priority, or hooks).  Two runs with equal fingerprints are expected to produce the
+/// same result, so a cached result may be reused.
+#[derive(Serialize)]
+pub struct ResultFingerprint<'a> {
+    /// Commit of the project that was checked out
+    pub project_hash: &'a str,
+    pub workdir: Option<&'a str>,
+    pub sparse_paths: Option<&'a [String]>,
+    pub prepare_script: Option<&'a str>,
+    /// The environment the prepare script left for Verus
+    pub prepare_env: &'a [(String, String)],
+    /// The project's toolchain, or the configuration's
+    pub rust_toolchain: Option<&'a str>,
+    /// Container image, if Verus runs in one
+    pub container: Option<&'a str>,
+    pub offline: bool,
+    pub cargo_home: Option<&'a Path>,
+    /// Commit of the Verus source tree, if it is a git checkout
+    pub verus_hash: Option<&'a str>,
+    /// See [`file_digest`]: the binary that ran, which under `--verus-binary` need not be
+    /// built from `verus_hash`.  Its contents rather than its path or modification time, so
+    /// that fingerprints match across machines and rebuilds
+    pub verus_binary_digest: &'a str,
+    /// The argument list passed to Verus, less [`PRESENTATION_ARGS`]
+    pub verus_args: &'a [String],
+    pub verus_features: &'a [String],
+    /// Solver executable to version
+    pub solver_versions: &'a BTreeMap<String, String>,
+}
+
+impl ResultFingerprint<'_> {
+    /// A hex SHA-256 digest of the fingerprint's JSON, which has a fixed field order, so
+    /// digests can be compared between runs and verita builds
+    pub fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(&serde_json::to_vec(self).expect("fingerprint serializes"));
+        hasher.finish_hex()
+    }
+}
+
+/// A hex SHA-256 digest of the contents of the file at `path`
+pub fn file_digest(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1 << 16];
+    loop {
+        match file.read(&mut buf)? {
+            0 => return Ok(hasher.finish_hex()),
+            n => hasher.update(&buf[..n]),
+        }
+    }
+}
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 (FIPS 180-4), for digests that must not change between Rust releases, as
+/// `DefaultHasher`'s may
+pub struct Sha256 {
+    state: [u32; 8],
+    block: Vec<u8>,
+    len: u64,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {
+            state: [
+                0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+                0x5be0cd19,
+            ],
+            block: Vec::with_capacity(64),
+            len: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        while !data.is_empty() {
+            let n = (64 - self.block.len()).min(data.len());
+            self.block.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if self.block.len() == 64 {
+                self.compress();
+            }
+        }
+    }
+
+    /// The digest of everything passed to [`Sha256::update`], as lowercase hex
+    pub fn finish_hex(mut self) -> String {
+        let bits = self.len.wrapping_mul(8);
+        self.block.push(0x80);
+        if self.block.len() > 56 {
+            self.block.resize(64, 0);
+            self.compress();
+        }
+        self.block.resize(56, 0);
+        self.block.extend_from_slice(&bits.to_be_bytes());
+        self.compress();
+        self.state.iter().map(|w| format!("{:08x}", w)).collect()
+    }
+
+    fn compress(&mut self) {
+        let mut w = [0u32; 64];
+        for (i, word) in self.block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+        self.block.clear();
+    }
+}
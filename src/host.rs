@@ -0,0 +1,36 @@
+use serde::Serialize;
+
+/// The machine a run happened on, so timings can be compared across hardware.  Fields that
+/// can't be determined on this platform are `None`
+#[derive(Debug, Clone, Serialize)]
+pub struct HostInfo {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub cpu_model: Option<String>,
+    pub logical_cores: Option<usize>,
+    pub total_memory_bytes: Option<u64>,
+}
+
+/// The value of the first `key: value` line for `key` in a `/proc` file
+fn proc_field(path: &str, key: &str) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    text.lines().find_map(|l| {
+        let (k, v) = l.split_once(':')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+impl HostInfo {
+    pub fn collect() -> Self {
+        HostInfo {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            cpu_model: proc_field("/proc/cpuinfo", "model name"),
+            logical_cores: std::thread::available_parallelism().ok().map(|n| n.get()),
+            // Reported in KiB, e.g. `MemTotal:       16318912 kB`
+            total_memory_bytes: proc_field("/proc/meminfo", "MemTotal")
+                .and_then(|v| v.trim_end_matches("kB").trim().parse::<u64>().ok())
+                .map(|kib| kib * 1024),
+        }
+    }
+}
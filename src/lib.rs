@@ -0,0 +1,2749 @@
+// The `runner` block in `run_project` is a single large `serde_json::json!` invocation
+#![recursion_limit = "256"]
+
+use crate::config::{RunConfiguration, RunConfigurationProject};
+use crate::output::VerusOutput;
+use anyhow::anyhow;
+use clap::Parser as ClapParser;
+use git2::Repository;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::{fs, path::Path, path::PathBuf, time::Duration};
+use tempdir::TempDir;
+use tracing::{error, info, warn}; // debug, trace
+use xshell::{cmd, Shell};
+
+pub mod archive;
+pub mod artifacts;
+pub mod baseline;
+pub mod cleanup;
+pub mod clone_cache;
+pub mod config;
+pub mod container;
+pub mod doctor;
+pub mod fingerprint;
+pub mod host;
+pub mod output;
+pub mod preflight;
+pub mod process;
+pub mod progress;
+pub mod report;
+pub mod revisions;
+
+#[derive(ClapParser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub run: Option<Args>,
+}
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Check that a Verus `--output-json` file matches the format Verita expects
+    CheckOutput {
+        /// Verus JSON output file
+        file: PathBuf,
+    },
+    /// Render a report from the per-project output JSON of an earlier run
+    Report {
+        /// The run's dated output directory
+        #[arg(long)]
+        from: PathBuf,
+        /// Write a Markdown table of the results here (`-` for stdout)
+        #[arg(long)]
+        markdown: Option<PathBuf>,
+        /// Write an HTML page of the results here, like a run's `report.html` (`-` for stdout)
+        #[arg(long)]
+        html: Option<PathBuf>,
+        /// Show durations in seconds with this many decimal places, instead of milliseconds
+        #[arg(long, value_name = "DECIMALS")]
+        seconds: Option<usize>,
+    },
+    /// Check the environment a run with these arguments needs, printing a pass, warn, or
+    /// fail line per check, without running anything
+    Doctor(Box<Args>),
+}
+
+#[derive(ClapParser)]
+pub struct Args {
+    /// Base of the Verus repository (the flag takes precedence over the environment)
+    #[arg(short, long, env = "VERITA_VERUS_REPO")]
+    pub verus_repo: PathBuf,
+    /// Verus binary to use instead of the one under --verus-repo's build directory
+    #[arg(long)]
+    pub verus_binary: Option<PathBuf>,
+    /// Path to the Singular algebra solver
+    #[arg(short, long)]
+    pub singular: Option<PathBuf>,
+    /// Path to a run configuration file (`-` to read it from stdin)
+    pub config: PathBuf,
+    /// Directory in which each run's dated output directory is created (the flag takes
+    /// precedence over the environment, which takes precedence over the default)
+    #[arg(long, env = "VERITA_OUTPUT_DIR", default_value = "output")]
+    pub output_dir: PathBuf,
+    /// Label for the run
+    #[arg(short, long)]
+    pub label: String,
+    /// Print debugging output (can be repeated for more detail)
+    #[arg(short = 'd', long = "debug", action = clap::ArgAction::Count)]
+    pub debug_level: u8,
+    /// strftime format for the run date used in output directory names.  Unless it has a
+    /// sub-second specifier (%f, %3f or %.3f), `-%3f` (milliseconds) is appended, so that
+    /// back-to-back runs don't collide
+    #[arg(long, default_value = "%Y-%m-%d-%H-%M-%S-%3f")]
+    pub timestamp_format: String,
+    /// Use local time rather than UTC for the run date
+    #[arg(long)]
+    pub local_time: bool,
+    /// Skip the preflight disk-space and connectivity checks
+    #[arg(long)]
+    pub no_preflight: bool,
+    /// Pack the run's output directory into a .tar.gz next to it
+    #[arg(long)]
+    pub archive: bool,
+    /// Like --archive, but remove the output directory once it is archived
+    #[arg(long)]
+    pub archive_only: bool,
+    /// Only verify projects whose checked-out commit is newer than this date (YYYY-MM-DD or
+    /// RFC 3339); older projects are recorded as unchanged
+    #[arg(long, value_parser = parse_since)]
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Append one JSON line per finished project to this file (`-` for stdout)
+    #[arg(long)]
+    pub stream: Option<PathBuf>,
+    /// Append project_started/project_finished events, one JSON object per line, to this file
+    /// or Unix socket
+    #[arg(long)]
+    pub events: Option<PathBuf>,
+    /// Stop starting new projects once the run has taken this many seconds
+    #[arg(long)]
+    pub max_runtime: Option<u64>,
+    /// Fail the run if any project's Verus output could not be parsed
+    #[arg(long)]
+    pub strict_output: bool,
+    /// Print the fully-resolved run configuration as TOML (with credentials redacted) and exit
+    #[arg(long)]
+    pub print_effective_config: bool,
+    /// Count a project as failed if Verus reports any warnings
+    #[arg(long)]
+    pub warnings_as_errors: bool,
+    /// Fail, rather than warn, when there are no projects to run
+    #[arg(long)]
+    pub error_on_empty: bool,
+    /// Only run projects with this tag (can be repeated: a project runs if it has any of them)
+    #[arg(long)]
+    pub tag: Vec<String>,
+    /// Skip projects with this tag (can be repeated; takes precedence over --tag)
+    #[arg(long)]
+    pub exclude_tag: Vec<String>,
+    /// Don't show the progress lines (shown only when stdout is a terminal)
+    #[arg(short, long)]
+    pub quiet: bool,
+    /// Skip projects whose refspec resolves to the same commit as in the last run that
+    /// verified them successfully with the same Verus build and configuration (tracked in
+    /// `revisions.json` under --cache-dir, if given, or else --output-dir)
+    #[arg(long)]
+    pub skip_unchanged_revs: bool,
+    /// Verify every project, ignoring --since and --skip-unchanged-revs
+    #[arg(long)]
+    pub force: bool,
+    /// Also write the aggregate run summary (metadata and each project's status and timing)
+    /// to this file
+    #[arg(long)]
+    pub summary_json: Option<PathBuf>,
+    /// Retry cloning (and updating `--cache-dir` mirrors) up to this many times on network
+    /// errors, waiting 1s, 2s, 4s, ... in between
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub clone_retries: u32,
+    /// Keep bare mirrors of the projects' repositories here between runs, and clone from
+    /// them after fetching what changed (see [`clone_cache::update`])
+    #[arg(long, value_name = "DIR")]
+    pub cache_dir: Option<PathBuf>,
+    /// When a project cloned from --cache-dir fails, clone it afresh, without the cache, and
+    /// run it once more, in case the cached checkout was at fault (no effect without
+    /// --cache-dir, whose clones are already fresh)
+    #[arg(long)]
+    pub reclone_on_failure: bool,
+    /// Check the configuration and each project's refspec, and print the Verus command line
+    /// each project would run, without cloning or running anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Also record the run's results in this SQLite database (see
+    /// [`output::SqliteSink`]), creating its tables if needed.  Needs the sqlite3 program on
+    /// the PATH
+    #[arg(long, value_name = "PATH")]
+    pub sqlite: Option<PathBuf>,
+    /// Don't write the per-project output JSON files
+    #[arg(long)]
+    pub no_per_project_files: bool,
+    /// Summary JSON (from --summary-json) of an earlier run, to report which projects'
+    /// commits have changed since then
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+    /// Only run the projects named in this file (one per line; `#` starts a comment)
+    #[arg(long)]
+    pub selection: Option<PathBuf>,
+    /// Only run this project; may be repeated
+    #[arg(long = "project", value_name = "NAME")]
+    pub projects: Vec<String>,
+    /// Only run projects whose names match this regular expression
+    #[arg(long, value_name = "REGEX")]
+    pub filter: Option<String>,
+    /// Run projects in a pseudo-random order derived from this seed, instead of priority and
+    /// declaration order, to expose order-dependent results
+    #[arg(long, value_name = "SEED")]
+    pub shuffle: Option<u64>,
+    /// Log every external command as a copy-pasteable shell command line
+    #[arg(long)]
+    pub verbose_commands: bool,
+    /// Check that every project's git_url is reachable and has its refspec, without cloning
+    /// or running anything, and report every problem
+    #[arg(long)]
+    pub check_reachability: bool,
+    /// Print a plain-English explanation of each project's result
+    #[arg(long)]
+    pub explain: bool,
+    /// Run each project with this many solver random seeds and report whether the outcome
+    /// depends on the seed
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub seeds: Option<u64>,
+    /// Ask Verus for a per-function timing breakdown and record how many functions were sent
+    /// to the solver (compared against `--baseline`)
+    #[arg(long)]
+    pub query_counts: bool,
+    /// Ask Verus for a per-function timing breakdown and save it, in the folded-stack format
+    /// used by flamegraph tools, as each project's `verus-profile.folded` artifact
+    #[arg(long)]
+    pub profile_verus: bool,
+    /// Save the SMT queries Verus generates as each project's `artifacts/smt`, and record a
+    /// digest of them; with --baseline, report which projects' queries changed
+    #[arg(long)]
+    pub capture_smt: bool,
+    /// Warn, rather than fail, when a solver's version differs from the configuration's
+    /// expected_z3_version or expected_cvc5_version
+    #[arg(long)]
+    pub lenient_solver_versions: bool,
+    /// Remove this field, given as a dot-separated path such as `runner.stderr`, from each
+    /// project's output JSON (can be repeated)
+    #[arg(long, value_name = "FIELD")]
+    pub redact: Vec<String>,
+    /// Verify up to this many projects at once, each in its own clone and shell; results
+    /// are still reported in configuration order
+    #[arg(long, default_value = "1")]
+    pub jobs: std::num::NonZeroUsize,
+    /// Warn if the Verus binary is more than this many hours old (it is always checked against
+    /// the Verus source's HEAD commit)
+    #[arg(long, value_name = "HOURS")]
+    pub max_binary_age: Option<u64>,
+    /// Fail, rather than warn, when the Verus binary looks stale
+    #[arg(long)]
+    pub fail_on_stale_binary: bool,
+    /// Only run the projects that failed (or, with --only-status, had the given statuses) in
+    /// the run whose output directory is FROM_DIR
+    #[arg(long, value_name = "FROM_DIR")]
+    pub only_failed: Option<PathBuf>,
+    /// With --only-failed, rerun the projects that had this status (can be repeated) instead
+    /// of those that failed, partially succeeded, failed to compile, or timed out
+    #[arg(long, value_enum, requires = "only_failed")]
+    pub only_status: Vec<ProjectStatus>,
+    /// Report projects where Verus hit a VIR error as infrastructure errors rather than
+    /// verification failures, and exit with status 2 if there are any
+    #[arg(long)]
+    pub vir_errors_as_infra: bool,
+}
+
+fn parse_since(s: &str) -> anyhow::Result<chrono::DateTime<chrono::Utc>> {
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(d.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    Ok(chrono::DateTime::parse_from_rfc3339(s)
+        .map_err(|e| anyhow!("invalid date {}: {}", s, e))?
+        .to_utc())
+}
+
+pub(crate) fn get_solver_version(
+    verus_repo: &PathBuf,
+    solver_exe: &str,
+    fmt_str: &str,
+) -> anyhow::Result<String> {
+    let sh = Shell::new()?;
+    let output = cmd!(sh, "{verus_repo}/source/{solver_exe} --version") //.quiet().run()?;
+        .output()?;
+    //dbg!(&output);
+    let v = parse_solver_version(&output.stdout, solver_exe, fmt_str)?;
+    println!("Found {solver_exe} version: {v}");
+    Ok(v)
+}
+
+/// Whether `actual` has `expected`'s dot-separated components as a prefix
+pub fn solver_version_matches(expected: &str, actual: &str) -> bool {
+    let actual: Vec<_> = actual.split('.').collect();
+    let expected: Vec<_> = expected.split('.').collect();
+    actual.starts_with(&expected)
+}
+
+/// Extract the version following `fmt_str` from a solver's `--version` output, tolerating
+/// non-UTF-8 bytes elsewhere in the banner
+pub fn parse_solver_version(
+    version_output: &[u8],
+    solver_exe: &str,
+    fmt_str: &str,
+) -> anyhow::Result<String> {
+    let output_str = String::from_utf8_lossy(version_output);
+    let fmt = format!("{fmt_str} ([0-9.]*) ");
+    let v = Regex::new(&fmt)?
+        .captures(&output_str)
+        .ok_or_else(|| anyhow!("Failed to find {solver_exe} version"))?
+        .get(1)
+        .expect("missing capture group")
+        .as_str()
+        .to_string();
+    Ok(v)
+}
+
+/// Format the run date, keeping millisecond precision so back-to-back runs don't collide
+fn format_run_date(timestamp_format: &str, local_time: bool) -> anyhow::Result<String> {
+    let mut fmt = timestamp_format.to_string();
+    if !fmt.contains("%3f") && !fmt.contains("%f") && !fmt.contains("%.3f") {
+        fmt.push_str("-%3f");
+    }
+    if chrono::format::StrftimeItems::new(&fmt).any(|i| matches!(i, chrono::format::Item::Error)) {
+        return Err(anyhow!("invalid timestamp format: {}", timestamp_format));
+    }
+    let date = if local_time {
+        chrono::Local::now().format(&fmt).to_string()
+    } else {
+        chrono::Utc::now().format(&fmt).to_string()
+    };
+    if date.is_empty()
+        || date.chars().any(|c| {
+            c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+        })
+    {
+        return Err(anyhow!(
+            "timestamp format {} produces a name that is not filesystem-safe: {}",
+            timestamp_format,
+            date
+        ));
+    }
+    Ok(date)
+}
+
+/// Remove the field at the dot-separated `path` (e.g. `runner.stderr`) from `json`, if present
+fn redact(json: &mut serde_json::Value, path: &str) {
+    let (parent, field) = match path.rsplit_once('.') {
+        Some((parent, field)) => (
+            json.pointer_mut(&format!("/{}", parent.replace('.', "/"))),
+            field,
+        ),
+        None => (Some(json), path),
+    };
+    if let Some(serde_json::Value::Object(map)) = parent {
+        map.remove(field);
+    }
+}
+
+fn write_output_json(path: &Path, output_json: &serde_json::Value) -> anyhow::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(output_json).unwrap())
+        .map_err(|e| anyhow!("cannot write output json: {}", e))
+}
+
+/// Verified functions per second of Verus time, across every project that reported both
+fn suite_functions_per_second<'a>(
+    project_summaries: impl IntoIterator<Item = &'a ProjectSummary>,
+) -> Option<f64> {
+    let (verified, total_ms) = project_summaries
+        .into_iter()
+        .filter_map(|p| p.verus_output.as_ref())
+        .fold((0, 0), |(v, t), o| {
+            (v + o.total_verified(), t + o.total_ms())
+        });
+    output::functions_per_second(verified, total_ms)
+}
+
+/// The projects in the earlier run's output directory `dir` whose status is one of
+/// `statuses`
+fn read_previous_statuses(
+    dir: &Path,
+    statuses: &[ProjectStatus],
+    run_configuration: &RunConfiguration,
+) -> anyhow::Result<BTreeSet<String>> {
+    let names: Vec<_> = statuses
+        .iter()
+        .filter_map(clap::ValueEnum::to_possible_value)
+        .map(|v| v.get_name().to_string())
+        .collect();
+    let mut selected = BTreeSet::new();
+    for report in report::load_output_dir(dir)? {
+        if !names.contains(&report.status) {
+            continue;
+        }
+        if run_configuration
+            .projects
+            .iter()
+            .any(|p| p.name == report.name)
+        {
+            selected.insert(report.name);
+        } else {
+            warn!(
+                "{} had status {} in {} but is no longer configured",
+                report.name,
+                report.status,
+                dir.display()
+            );
+        }
+    }
+    info!(
+        "rerunning the projects that had status {} in {}: {}",
+        names.join(" or "),
+        dir.display(),
+        selected.iter().cloned().collect::<Vec<_>>().join(", ")
+    );
+    Ok(selected)
+}
+
+/// The project names listed in a `--selection` file, one per line, ignoring blank lines and
+/// `#` comments; each must name a project in the configuration
+fn read_selection(
+    path: &Path,
+    run_configuration: &RunConfiguration,
+) -> anyhow::Result<BTreeSet<String>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("cannot read selection {}: {}", path.display(), e))?;
+    let names: BTreeSet<String> = text
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.to_string())
+        .collect();
+    if let Some(unknown) = names
+        .iter()
+        .find(|n| !run_configuration.projects.iter().any(|p| &&p.name == n))
+    {
+        return Err(anyhow!(
+            "selection {} names unknown project {}",
+            path.display(),
+            unknown
+        ));
+    }
+    info!(
+        "selection {}: {}",
+        path.display(),
+        names.iter().cloned().collect::<Vec<_>>().join(", ")
+    );
+    Ok(names)
+}
+
+/// Deterministic Fisher-Yates shuffle driven by SplitMix64, so `--shuffle` orders are
+/// reproducible across platforms and releases
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        items.swap(i, (next() % (i as u64 + 1)) as usize);
+    }
+}
+
+/// Fraction of all functions, across every project that reported counts, that verified
+fn suite_pass_ratio<'a>(
+    project_summaries: impl IntoIterator<Item = &'a ProjectSummary>,
+) -> Option<f64> {
+    let (verified, errors) = project_summaries
+        .into_iter()
+        .filter_map(|p| p.verus_output.as_ref())
+        .fold((0, 0), |(v, e), o| {
+            (v + o.total_verified(), e + o.total_errors())
+        });
+    output::pass_ratio(verified, errors)
+}
+
+/// The run's metadata and each project's status and timing, without the per-project details
+fn summary_json(
+    ctx: &RunContext,
+    project_summaries: &[&ProjectSummary],
+    duration: Duration,
+    complete: bool,
+) -> serde_json::Value {
+    let count = |status| {
+        project_summaries
+            .iter()
+            .filter(|p| p.status == status)
+            .count()
+    };
+    serde_json::json!({
+        "label": ctx.args.label,
+        "description": ctx.run_configuration.description,
+        "date": ctx.date,
+        "verus_hash": ctx.verus_hash,
+        "verus_binary_mtime": ctx.verus_binary_mtime,
+        "host": ctx.host,
+        "shuffle_seed": ctx.args.shuffle,
+        // False while the run is still in progress (or if it died)
+        "complete": complete,
+        "duration_ms": duration.as_millis() as u64,
+        "pass_ratio": suite_pass_ratio(project_summaries.iter().copied()),
+        "functions_per_second": suite_functions_per_second(project_summaries.iter().copied()),
+        "estimated_cpu_time_ms": project_summaries
+            .iter()
+            .filter_map(|p| p.verus_output.as_ref()?.estimated_cpu_time_ms())
+            .sum::<u64>(),
+        "counts": {
+            "success": count(ProjectStatus::Success),
+            "partial_success": count(ProjectStatus::PartialSuccess),
+            "failure": count(ProjectStatus::Failure),
+            "compile_failed": count(ProjectStatus::CompileFailed),
+            "allowed_failure": count(ProjectStatus::AllowedFailure),
+            "unchanged": count(ProjectStatus::Unchanged),
+            "not_run": count(ProjectStatus::NotRun),
+            "infrastructure_error": count(ProjectStatus::InfrastructureError),
+            "timed_out": count(ProjectStatus::TimedOut),
+        },
+        "vir_errors": count_vir_errors(project_summaries.iter().copied()),
+        "projects": project_summaries
+            .iter()
+            .map(|p| p.summary_entry_json())
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// What `project`'s last verified commit is kept under in [`revisions::RevisionState`]: its
+/// name and a digest of the Verus build and of its configuration, so that neither a new Verus
+/// build nor another configuration sharing the `--cache-dir` finds it already verified
+fn revision_state_key(ctx: &RunContext, project: &RunConfigurationProject) -> String {
+    let verus_args: Vec<String> = verus_args(ctx.args, ctx.run_configuration, project)
+        .into_iter()
+        .filter(|a| !fingerprint::PRESENTATION_ARGS.contains(&a.as_str()))
+        .collect();
+    let setup = serde_json::json!({
+        "project": project,
+        "verus_hash": ctx.verus_hash,
+        "verus_binary_digest": ctx.verus_binary_digest,
+        "verus_features": ctx.run_configuration.verus_features,
+        "verus_args": verus_args,
+        "solver_versions": ctx.solver_versions,
+    });
+    let mut hasher = fingerprint::Sha256::new();
+    hasher.update(setup.to_string().as_bytes());
+    format!("{} {}", project.name, hasher.finish_hex())
+}
+
+/// The arguments Verus is run with on `project`, before any that only affect logging (e.g.,
+/// `--capture-smt`'s)
+fn verus_args(
+    args: &Args,
+    run_configuration: &RunConfiguration,
+    project: &RunConfigurationProject,
+) -> Vec<String> {
+    let mut verus_args: Vec<String> = ["--output-json", "--time", project.crate_root.as_str()]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+    if !run_configuration.report_long_running {
+        verus_args.push("--no-report-long-running".to_string());
+    }
+    if args.query_counts || args.profile_verus {
+        verus_args.push("--time-expanded".to_string());
+    }
+    verus_args.extend(project.solver.unwrap_or_default().verus_args());
+    if let Some(ms) = run_configuration.effective_solver_timeout_ms(project) {
+        verus_args.extend(["--smt-option".to_string(), format!("timeout={}", ms)]);
+    }
+    verus_args.extend(project.verify_module_args());
+    if project.compile {
+        verus_args.push("--compile".to_string());
+    }
+    verus_args.extend(run_configuration.effective_args(project).cloned());
+    // Under --seeds, the main run uses seed 0
+    if args.seeds.is_some() {
+        verus_args.extend(seed_args(0));
+    }
+    verus_args
+}
+
+/// The arguments that select the solver's random seed, which [`verus_args`] ends with under
+/// `--seeds`
+fn seed_args(seed: u64) -> Vec<String> {
+    vec![
+        "--smt-option".to_string(),
+        format!("smt.random_seed={}", seed),
+    ]
+}
+
+/// Run `attempt`, retrying up to `retries` times with exponential backoff while it fails
+/// with a network error (see [`is_network_error`])
+fn with_network_retries<T>(
+    retries: u32,
+    what: &str,
+    mut attempt: impl FnMut() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let mut retried = 0;
+    loop {
+        match attempt() {
+            Err(e) if retried < retries && is_network_error(&e) => {
+                let backoff = Duration::from_secs(1 << retried.min(10));
+                retried += 1;
+                info!(
+                    "\t{} failed ({}); retrying in {}s ({} of {})",
+                    what,
+                    e,
+                    backoff.as_secs(),
+                    retried,
+                    retries
+                );
+                std::thread::sleep(backoff);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether `e` is a transport error from git, which may not recur, rather than one that
+/// would (e.g., a refspec that can't be resolved).  libgit2 reports socket errors, such as a
+/// refused connection, as system (`Os`) errors
+pub fn is_network_error(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<git2::Error>().is_some_and(|e| {
+        matches!(
+            e.class(),
+            git2::ErrorClass::Net
+                | git2::ErrorClass::Http
+                | git2::ErrorClass::Ssl
+                | git2::ErrorClass::Ssh
+                | git2::ErrorClass::Os
+        )
+    })
+}
+
+/// Clone `project` into `repo_path` (from `mirror`, a local copy of its repository, if
+/// given) and resolve `refspec` in the clone.  Under `clone_depth`, a clone from the
+/// project's repository is shallow, with the refspec fetched directly when it is not in the
+/// branches' recent history (e.g., an older commit hash); if that fails, this falls back to a
+/// full clone.  The flag is whether the clone is shallow
+fn clone_project(
+    project: &RunConfigurationProject,
+    repo_path: &Path,
+    refspec: &str,
+    mirror: Option<&Path>,
+) -> anyhow::Result<(Repository, git2::Oid, bool)> {
+    let fetch_options = |depth: Option<u32>| {
+        let mut options = git2::FetchOptions::new();
+        if let Some(depth) = depth {
+            options.depth(depth as i32);
+        }
+        options
+    };
+    let clone = |depth: Option<u32>| {
+        let mut builder = git2::build::RepoBuilder::new();
+        // The working tree is populated by the (sparse) checkout of the refspec afterwards
+        if project.sparse_paths.is_some() {
+            let mut no_checkout = git2::build::CheckoutBuilder::new();
+            no_checkout.dry_run();
+            builder.with_checkout(no_checkout);
+        }
+        builder.fetch_options(fetch_options(depth));
+        match mirror {
+            Some(mirror) => {
+                let repo = builder.clone(&mirror.display().to_string(), repo_path)?;
+                // So that prepare scripts see (and fetch from) the project's repository
+                repo.remote_set_url("origin", &project.git_url)?;
+                Ok(repo)
+            }
+            None => builder.clone(&project.git_url, repo_path),
+        }
+    };
+    let resolve = |repo: &Repository| repo.revparse_single(refspec).map(|rev| rev.id());
+    // A local clone copies everything anyway
+    if let Some(depth) = project.clone_depth.filter(|_| mirror.is_none()) {
+        let shallow = clone(Some(depth)).and_then(|repo| {
+            let rev = match resolve(&repo) {
+                Ok(rev) => rev,
+                Err(_) => {
+                    repo.find_remote("origin")?.fetch(
+                        &[refspec],
+                        Some(&mut fetch_options(Some(depth))),
+                        None,
+                    )?;
+                    resolve(&repo)
+                        .or_else(|_| repo.revparse_single("FETCH_HEAD").map(|r| r.id()))?
+                }
+            };
+            Ok((repo, rev))
+        });
+        match shallow {
+            Ok((repo, rev)) => return Ok((repo, rev, true)),
+            Err(e) => {
+                warn!(
+                    "shallow clone of {} at {} failed ({}); cloning in full",
+                    project.name, refspec, e
+                );
+                if repo_path.exists() {
+                    fs::remove_dir_all(repo_path)?;
+                }
+            }
+        }
+    }
+    let repo = clone(None)?;
+    let rev = resolve(&repo).map_err(|e| anyhow!("failed to find {}: {}", refspec, e))?;
+    Ok((repo, rev, false))
+}
+
+/// Check out only `paths` of `rev`, plus what Cargo needs to build the crate
+fn sparse_checkout(
+    repo: &Repository,
+    rev: &git2::Object,
+    project: &RunConfigurationProject,
+    paths: &[String],
+) -> anyhow::Result<()> {
+    let workdir = Path::new(project.workdir.as_deref().unwrap_or(""));
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.force();
+    for path in paths {
+        checkout.path(path);
+    }
+    for dir in [Path::new(""), workdir] {
+        for file in [
+            "Cargo.toml",
+            "Cargo.lock",
+            "rust-toolchain",
+            "rust-toolchain.toml",
+        ] {
+            checkout.path(dir.join(file));
+        }
+    }
+    repo.checkout_tree(rev, Some(&mut checkout))?;
+    let crate_root = project.crate_root_in_clone();
+    match repo.workdir() {
+        Some(clone) if clone.join(&crate_root).is_file() => Ok(()),
+        _ => Err(anyhow!(
+            "{} is not in the checked-out paths",
+            crate_root.display()
+        )),
+    }
+}
+
+/// Parse the `KEY=VALUE` lines a prepare script writes to `VERITA_PREPARE_ENV`
+fn read_env_file(path: &Path) -> anyhow::Result<Vec<(String, String)>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("cannot read {}: {}", path.display(), e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .map(|(i, l)| match l.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => Ok((k.trim().to_string(), v.to_string())),
+            _ => Err(anyhow!(
+                "{}:{}: expected KEY=VALUE, got {:?}",
+                path.display(),
+                i + 1,
+                l
+            )),
+        })
+        .collect()
+}
+
+/// Write to a temporary file and rename it into place, so readers (and a crash) never see
+/// a partially written file
+fn write_json_atomically(path: &Path, output_json: &serde_json::Value) -> anyhow::Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    write_output_json(&tmp_path, output_json)?;
+    std::fs::rename(&tmp_path, path).map_err(|e| {
+        anyhow!(
+            "cannot rename {} to {}: {}",
+            tmp_path.display(),
+            path.display(),
+            e
+        )
+    })
+}
+
+/// Open an append-only line sink: stdout for `-`, a connection for a Unix socket, and
+/// otherwise a file
+fn open_sink(p: &Path) -> anyhow::Result<Box<dyn Write>> {
+    if p.as_os_str() == "-" {
+        return Ok(Box::new(std::io::stdout()));
+    }
+    if fs::metadata(p).is_ok_and(|m| m.file_type().is_socket()) {
+        let socket = std::os::unix::net::UnixStream::connect(p)
+            .map_err(|e| anyhow!("cannot connect to {}: {}", p.display(), e))?;
+        return Ok(Box::new(socket));
+    }
+    Ok(Box::new(
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(p)
+            .map_err(|e| anyhow!("cannot open {}: {}", p.display(), e))?,
+    ))
+}
+
+fn write_event(
+    events: &mut Box<dyn Write>,
+    project: &str,
+    phase: &str,
+    status: Option<ProjectStatus>,
+) -> anyhow::Result<()> {
+    let mut event = serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "project": project,
+        "phase": phase,
+    });
+    if let Some(status) = status {
+        event["status"] = serde_json::json!(status);
+    }
+    writeln!(events, "{}", event)?;
+    events.flush()?;
+    Ok(())
+}
+
+/// Whether Verus's stderr shows the solver running out of memory, which, unlike a genuine
+/// verification failure, may succeed when retried
+pub fn looks_like_solver_oom(stderr: &[u8]) -> bool {
+    let stderr = String::from_utf8_lossy(stderr).to_lowercase();
+    ["out of memory", "bad_alloc", "cannot allocate memory"]
+        .iter()
+        .any(|s| stderr.contains(s))
+}
+
+/// Quote `arg` for a POSIX shell, leaving it bare when that is already safe
+fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// A copy-pasteable shell command line equivalent to `cmd`, including its working
+/// directory and any environment variables it sets
+pub fn shell_command_line(cmd: &std::process::Command) -> String {
+    let mut words = Vec::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        words.push(format!("cd {} &&", shell_quote(&dir.to_string_lossy())));
+    }
+    for (k, v) in cmd.get_envs() {
+        if let Some(v) = v {
+            words.push(format!(
+                "{}={}",
+                k.to_string_lossy(),
+                shell_quote(&v.to_string_lossy())
+            ));
+        }
+    }
+    words.push(shell_quote(&cmd.get_program().to_string_lossy()));
+    words.extend(cmd.get_args().map(|a| shell_quote(&a.to_string_lossy())));
+    words.join(" ")
+}
+
+/// Target for the copy-pasteable command lines enabled by `--verbose-commands`
+pub const COMMANDS_LOG_TARGET: &str = "verita::commands";
+
+pub fn log_command(cmd: std::process::Command) -> std::process::Command {
+    info!("running: {:?}", &cmd);
+    info!(target: COMMANDS_LOG_TARGET, "$ {}", shell_command_line(&cmd));
+    cmd
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum ProjectStatus {
+    Success,
+    /// Verus succeeded, but only verified part of the crate, so the counts are partial
+    PartialSuccess,
+    Failure,
+    /// Verification succeeded but compiling the verified code (`compile = true`) failed
+    CompileFailed,
+    /// Failed (or failed to compile), but the project is marked `allow_failure`, so this is
+    /// reported without counting against the run
+    AllowedFailure,
+    /// Not verified: no commits since `--since`, or the same commit as the last run under
+    /// `--skip-unchanged-revs`
+    Unchanged,
+    /// Not started because the `--max-runtime` budget was exhausted
+    NotRun,
+    /// Verus itself failed (a VIR error, under `--vir-errors-as-infra`), or the project could
+    /// not be cloned, set up, or run, so the result says nothing about the proofs
+    InfrastructureError,
+    /// Verus was killed after running longer than the project's `timeout_seconds`
+    TimedOut,
+}
+
+pub struct ProjectSummary {
+    pub project: RunConfigurationProject,
+    pub status: ProjectStatus,
+    pub hash: String,
+    pub verification_duration: Duration,
+    pub verus_output: Option<VerusOutput>,
+    /// See [`fingerprint::ResultFingerprint`]; `None` if the project was not verified
+    pub result_fingerprint: Option<String>,
+    /// Under `--seeds`, whether every seed produced the same outcome
+    pub seed_stable: Option<bool>,
+    /// On-disk size of the checked-out clone; `None` if not cloned or not measurable
+    pub clone_size_bytes: Option<u64>,
+    /// Under `--baseline`, how the project's commit changed since then (see
+    /// [`baseline::Baseline::revision_change`])
+    pub revision_change: Option<String>,
+    /// Under `--capture-smt`, see [`artifacts::smt_digest`]
+    pub smt_hash: Option<String>,
+}
+
+impl ProjectSummary {
+    /// A one-line, self-contained description of the result, for `--stream`
+    pub fn stream_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.project.name,
+            "status": self.status,
+            "hash": self.hash,
+            "revision_change": self.revision_change,
+            "smt_hash": self.smt_hash,
+            "smt_function_count": self
+                .verus_output
+                .as_ref()
+                .and_then(|o| o.smt_function_count()),
+            "verification_duration_ms": self.verification_duration.as_millis() as u64,
+            "pass_ratio": self.verus_output.as_ref().and_then(|o| o.pass_ratio()),
+            "functions_per_second": self
+                .verus_output
+                .as_ref()
+                .and_then(|o| o.functions_per_second()),
+            "estimated_cpu_time_ms": self
+                .verus_output
+                .as_ref()
+                .and_then(|o| o.estimated_cpu_time_ms()),
+        })
+    }
+
+    /// [`Self::stream_json`] plus the counts and the parsed Verus output, for the run's
+    /// `summary.json`
+    pub fn summary_entry_json(&self) -> serde_json::Value {
+        let mut entry = self.stream_json();
+        entry["success"] = serde_json::json!(matches!(
+            self.status,
+            ProjectStatus::Success | ProjectStatus::PartialSuccess
+        ));
+        entry["verified"] =
+            serde_json::json!(self.verus_output.as_ref().map(|o| o.total_verified()));
+        entry["errors"] = serde_json::json!(self.verus_output.as_ref().map(|o| o.total_errors()));
+        entry["verus_output"] = serde_json::json!(self.verus_output);
+        entry
+    }
+
+    /// A plain-English reading of the result, for `--explain`
+    pub fn explanation(&self) -> String {
+        let output = match self.status {
+            ProjectStatus::NotRun => {
+                return "not run: the --max-runtime budget was used up before it started".into()
+            }
+            ProjectStatus::Unchanged => {
+                return "skipped: unchanged (by --since or --skip-unchanged-revs)".into()
+            }
+            ProjectStatus::InfrastructureError if self.verus_output.is_none() => {
+                return "could not be cloned, set up, or run (see the output JSON)".into()
+            }
+            ProjectStatus::TimedOut => {
+                return format!(
+                    "timed out: Verus was killed after {}s",
+                    self.verification_duration.as_secs()
+                )
+            }
+            _ => match &self.verus_output {
+                Some(output) => output,
+                None if self.status == ProjectStatus::AllowedFailure => {
+                    return "allowed failure: Verus produced no parseable output (see stderr)"
+                        .into()
+                }
+                None => return "Verus produced no parseable output (see stderr)".into(),
+            },
+        };
+        let (verified, errors) = (output.total_verified(), output.total_errors());
+        // Allowed failures are explained like any other failure
+        let status = match self.status {
+            ProjectStatus::AllowedFailure => ProjectStatus::Failure,
+            status => status,
+        };
+        let explanation: String = match status {
+            _ if output.encountered_vir_error() => {
+                "Verus crashed with an internal error (see stderr)".into()
+            }
+            _ if errors > 0 => format!(
+                "{} of {} functions failed to verify",
+                errors,
+                verified + errors
+            ),
+            ProjectStatus::CompileFailed => format!(
+                "all {} functions verified, but compiling the verified code failed (see stderr)",
+                verified
+            ),
+            ProjectStatus::PartialSuccess => format!(
+                "all {} functions checked verified, but only part of the crate was checked",
+                verified
+            ),
+            ProjectStatus::Failure if output.total_warnings() > 0 => format!(
+                "all {} functions verified, but Verus reported {} warnings",
+                verified,
+                output.total_warnings()
+            ),
+            ProjectStatus::Failure => {
+                "Verus failed without reporting a verification error (see stderr)".into()
+            }
+            _ => format!("all {} functions verified", verified),
+        };
+        if self.status == ProjectStatus::AllowedFailure {
+            format!("allowed failure: {}", explanation)
+        } else {
+            explanation
+        }
+    }
+}
+
+/// Projects where Verus reported a VIR error, whatever their status
+fn count_vir_errors<'a>(project_summaries: impl IntoIterator<Item = &'a ProjectSummary>) -> usize {
+    project_summaries
+        .into_iter()
+        .filter(|p| {
+            p.verus_output
+                .as_ref()
+                .is_some_and(|o| o.encountered_vir_error())
+        })
+        .count()
+}
+
+/// Projects that ran Verus but whose output could not be parsed into a [`VerusOutput`]
+fn count_unparseable(project_summaries: &[ProjectSummary]) -> usize {
+    project_summaries
+        .iter()
+        .filter(|p| {
+            matches!(
+                p.status,
+                ProjectStatus::Success | ProjectStatus::PartialSuccess | ProjectStatus::Failure
+            ) && p.verus_output.is_none()
+        })
+        .count()
+}
+
+pub struct RunSummary {
+    /// The dated directory holding the per-project output JSON
+    pub output_path: PathBuf,
+    pub project_summaries: Vec<ProjectSummary>,
+    /// Set when the output directory was archived
+    pub archive_path: Option<PathBuf>,
+}
+
+impl RunSummary {
+    pub fn unparseable_output_count(&self) -> usize {
+        count_unparseable(&self.project_summaries)
+    }
+
+    /// Projects that failed to verify, failed to compile, or timed out.  Allowed failures
+    /// don't count
+    pub fn failure_count(&self) -> usize {
+        self.project_summaries
+            .iter()
+            .filter(|p| {
+                matches!(
+                    p.status,
+                    ProjectStatus::Failure | ProjectStatus::CompileFailed | ProjectStatus::TimedOut
+                )
+            })
+            .count()
+    }
+
+    pub fn infrastructure_error_count(&self) -> usize {
+        self.project_summaries
+            .iter()
+            .filter(|p| p.status == ProjectStatus::InfrastructureError)
+            .count()
+    }
+}
+
+/// Read, parse, and validate the run configuration named by `args.config`
+pub fn load_run_configuration(args: &Args) -> anyhow::Result<RunConfiguration> {
+    let config_from_stdin = args.config.as_os_str() == "-";
+    let config_text = if config_from_stdin {
+        std::io::read_to_string(std::io::stdin())
+            .map_err(|e| anyhow!("cannot read configuration from stdin: {}", e))?
+    } else {
+        std::fs::read_to_string(&args.config).map_err(|e| {
+            anyhow!(
+                "cannot read configuration file {}: {}",
+                args.config.display(),
+                e
+            )
+        })?
+    };
+    let config_source = if config_from_stdin {
+        "stdin".to_string()
+    } else {
+        args.config.display().to_string()
+    };
+    let mut run_configuration: RunConfiguration = toml::from_str(&config_text).map_err(|e| {
+        anyhow!(
+            "cannot parse run configuration from {}: {}",
+            config_source,
+            e
+        )
+    })?;
+    // Paths in the configuration are relative to the file (or, for stdin, the current directory)
+    let config_dir = if config_from_stdin {
+        Path::new(".")
+    } else {
+        args.config.parent().unwrap_or(Path::new("."))
+    };
+    run_configuration.load_extra_args_files(config_dir)?;
+    run_configuration.validate()?;
+    Ok(run_configuration)
+}
+
+/// Target for the final one-line run summary, which is logged regardless of verbosity
+pub const SUMMARY_LOG_TARGET: &str = "verita::summary";
+
+/// What [`run_project`] needs from the surrounding run: its options, configuration, and
+/// environment, and the state shared between projects
+pub struct RunContext<'a> {
+    pub args: &'a Args,
+    pub run_configuration: &'a RunConfiguration,
+    /// The solver and Cargo environment, set in each project's shell
+    pub shell_env: Vec<(String, std::ffi::OsString)>,
+    pub verus_binary_path: PathBuf,
+    /// When the Verus binary was last modified (RFC 3339)
+    pub verus_binary_mtime: String,
+    /// See [`fingerprint::file_digest`]
+    pub verus_binary_digest: String,
+    /// Commit of the Verus source tree, if it is a git checkout
+    pub verus_hash: Option<String>,
+    /// Solver executable to version
+    pub solver_versions: BTreeMap<String, String>,
+    /// The run's formatted date, recorded with each result
+    pub date: String,
+    /// Directory receiving per-project output JSON
+    pub output_path: PathBuf,
+    /// Scratch directory into which projects are cloned
+    pub workdir: PathBuf,
+    pub baseline: Option<baseline::Baseline>,
+    /// Collected once per run
+    pub host: host::HostInfo,
+    /// Set when the configuration has a `container` image
+    pub container: Option<container::Container>,
+    pub output_transform: Option<OutputTransform>,
+    pub revisions: Mutex<revisions::RevisionState>,
+    /// Retries spent so far, against `max_total_retries`
+    pub total_retries: AtomicU32,
+    pub retry_budget_exhausted: AtomicBool,
+    /// By [`clone_cache::cache_key`], held while updating and cloning from that mirror in
+    /// `--cache-dir`, which projects sharing a repository would otherwise update at once
+    pub clone_cache_locks: Mutex<BTreeMap<String, Arc<Mutex<()>>>>,
+}
+
+impl RunContext<'_> {
+    /// A shell of the project's own, so that changing its directory affects no other
+    /// (possibly concurrent) project, with `shell_env` set
+    fn shell(&self) -> anyhow::Result<Shell> {
+        let sh = Shell::new()?;
+        for (key, value) in self.shell_env.iter() {
+            sh.set_var(key, value);
+        }
+        Ok(sh)
+    }
+
+    /// `cmd`, run in the configured container if there is one, named for `project`
+    fn in_container(
+        &self,
+        project: &RunConfigurationProject,
+        cmd: std::process::Command,
+    ) -> std::process::Command {
+        match &self.container {
+            Some(container) => container.wrap(&cmd, &container::Container::name(&project.name)),
+            None => cmd,
+        }
+    }
+
+    /// The lock for `git_url`'s mirror in `--cache-dir` (see `clone_cache_locks`)
+    fn clone_cache_lock(&self, git_url: &str) -> Arc<Mutex<()>> {
+        let mut locks = self.clone_cache_locks.lock().unwrap();
+        locks
+            .entry(clone_cache::cache_key(git_url))
+            .or_default()
+            .clone()
+    }
+
+    /// Stop `project`'s container, if commands run in one
+    fn kill_container(&self, project: &RunConfigurationProject) {
+        if let Some(container) = &self.container {
+            container.kill(&container::Container::name(&project.name));
+        }
+    }
+
+    /// Under --no-per-project-files, results only reach the summary (see --summary-json).
+    /// The JSON, including its `runner` block, is complete by now; the `output_transform`
+    /// sees it first, and `--redact` then applies to what it returns
+    fn write_project_json(
+        &self,
+        project: &RunConfigurationProject,
+        path: &Path,
+        output_json: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        if self.args.no_per_project_files {
+            return Ok(());
+        }
+        let mut output_json = output_json.clone();
+        if let Some(transform) = &self.output_transform {
+            transform(project, &mut output_json);
+        }
+        for field in self.args.redact.iter() {
+            redact(&mut output_json, field);
+        }
+        write_output_json(path, &output_json)
+    }
+}
+
+/// Clone, prepare, and verify one project, writing its output JSON under the run's output
+/// directory.  [`run`] calls this for each selected project; other tools can use it to
+/// schedule projects themselves
+pub fn run_project(
+    project: &RunConfigurationProject,
+    ctx: &RunContext,
+) -> anyhow::Result<ProjectSummary> {
+    let result = run_project_cloned(project, ctx, false);
+    let failed = result.as_ref().map_or(true, |summary| {
+        matches!(
+            summary.status,
+            ProjectStatus::Failure | ProjectStatus::CompileFailed | ProjectStatus::AllowedFailure
+        )
+    });
+    if !(ctx.args.reclone_on_failure && ctx.args.cache_dir.is_some() && failed) {
+        return result;
+    }
+    warn!(
+        "{} failed after cloning from the clone cache; recloning it directly and running it again",
+        project.name
+    );
+    run_project_cloned(project, ctx, true)
+}
+
+/// [`run_project`], cloning directly rather than from `--cache-dir` if `recloned`
+fn run_project_cloned(
+    project: &RunConfigurationProject,
+    ctx: &RunContext,
+    recloned: bool,
+) -> anyhow::Result<ProjectSummary> {
+    let RunContext {
+        args,
+        run_configuration,
+        verus_binary_path,
+        verus_binary_mtime,
+        verus_binary_digest,
+        verus_hash,
+        solver_versions,
+        date,
+        output_path,
+        workdir,
+        baseline,
+        host,
+        ..
+    } = ctx;
+    let z3_version = &solver_versions["z3"];
+    let cvc5_version = &solver_versions["cvc5"];
+    info!("running project {}", project.name);
+
+    info!("\tCloning project");
+    //let repo_path = workdir.path().join(&project.name);
+    let repo_path = workdir.join(&project.name);
+    let refspec = run_configuration
+        .effective_refspec(project)
+        .expect("validated refspec");
+    let clone_directly = || {
+        with_network_retries(
+            args.clone_retries,
+            &format!("cloning {}", project.name),
+            || {
+                if repo_path.exists() {
+                    cleanup::remove_dir_all_resilient(&repo_path)?;
+                }
+                clone_project(project, &repo_path, refspec, None)
+            },
+        )
+        .map(|(repo, rev, shallow)| (repo, rev, shallow, false))
+    };
+    let cloned = match args.cache_dir.as_ref().filter(|_| !recloned) {
+        Some(cache_dir) => {
+            let lock = ctx.clone_cache_lock(&project.git_url);
+            let _mirror = lock.lock().unwrap();
+            let mirror = with_network_retries(
+                args.clone_retries,
+                &format!("updating the clone cache for {}", project.name),
+                || clone_cache::update(cache_dir, &project.git_url),
+            );
+            match mirror {
+                Ok(mirror) => clone_project(project, &repo_path, refspec, Some(&mirror))
+                    .map(|(repo, rev, _)| (repo, rev, false, true)),
+                Err(e) => {
+                    warn!(
+                        "cannot update the clone cache for {} ({}); cloning directly",
+                        project.name, e
+                    );
+                    clone_directly()
+                }
+            }
+        }
+        None => clone_directly(),
+    };
+    let (project_repo, rev, shallow_clone, cloned_from_cache) = match cloned {
+        Ok(cloned) => cloned,
+        // The other projects can still run
+        Err(e) => return failed_project(project, ctx, "clone", &e),
+    };
+    let rev = project_repo.find_object(rev, None)?;
+    let sparse_checkout = match &project.sparse_paths {
+        Some(paths) => match sparse_checkout(&project_repo, &rev, project, paths) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(
+                    "sparse checkout of {} failed ({}); checking out everything",
+                    project.name, e
+                );
+                project_repo
+                    .checkout_tree(&rev, Some(git2::build::CheckoutBuilder::new().force()))?;
+                false
+            }
+        },
+        None => {
+            project_repo.checkout_tree(&rev, None)?;
+            false
+        }
+    };
+    let hash = rev.id().to_string();
+    let sh = &ctx.shell()?;
+    sh.change_dir(&repo_path);
+    let revision_change = baseline
+        .as_ref()
+        .and_then(|b| b.revision_change(&project.name, &hash));
+    if let Some(change) = revision_change.as_deref().filter(|c| *c != "unchanged") {
+        info!("\t{} moved {} since the baseline", project.name, change);
+    }
+    let clone_size_bytes = match preflight::dir_size_bytes(&repo_path) {
+        Ok(size) => Some(size),
+        Err(e) => {
+            warn!("cannot measure the clone size of {}: {}", project.name, e);
+            None
+        }
+    };
+
+    if args.skip_unchanged_revs
+        && !args.force
+        && ctx
+            .revisions
+            .lock()
+            .unwrap()
+            .get(&revision_state_key(ctx, project))
+            == Some(hash.as_str())
+    {
+        info!(
+            "\tSkipping {}: {} still resolves to {}, as in the last run",
+            project.name, refspec, hash
+        );
+        let output_json = serde_json::json!({
+            "runner": {
+                "unchanged": true,
+                "status": ProjectStatus::Unchanged,
+                "unchanged_revision": true,
+                "refspec": refspec,
+                "clone_size_bytes": clone_size_bytes,
+                "revision_change": revision_change,
+                "run_configuration": project,
+                "label": args.label,
+                "date": date,
+            }
+        });
+        ctx.write_project_json(
+            project,
+            &output_path.join(&project.name).with_extension("json"),
+            &output_json,
+        )?;
+        return Ok(ProjectSummary {
+            project: project.clone(),
+            status: ProjectStatus::Unchanged,
+            hash,
+            verification_duration: Duration::ZERO,
+            verus_output: None,
+            result_fingerprint: None,
+            seed_stable: None,
+            clone_size_bytes,
+            revision_change,
+            smt_hash: None,
+        });
+    }
+    if let Some(since) = args.since.filter(|_| !args.force) {
+        let commit_time = rev.peel_to_commit()?.time().seconds();
+        if commit_time < since.timestamp() {
+            info!("\tNo commits since {}, skipping", since);
+            let output_json = serde_json::json!({
+                "runner": {
+                    "unchanged": true,
+                    "status": ProjectStatus::Unchanged,
+                    "since": since.to_rfc3339(),
+                    "commit_time": commit_time,
+                    "clone_size_bytes": clone_size_bytes,
+                    "revision_change": revision_change,
+                    "run_configuration": project,
+                    "label": args.label,
+                    "date": date,
+                }
+            });
+            ctx.write_project_json(
+                project,
+                &output_path.join(&project.name).with_extension("json"),
+                &output_json,
+            )?;
+            return Ok(ProjectSummary {
+                project: project.clone(),
+                status: ProjectStatus::Unchanged,
+                hash,
+                verification_duration: Duration::ZERO,
+                verus_output: None,
+                result_fingerprint: None,
+                seed_stable: None,
+                clone_size_bytes,
+                revision_change,
+                smt_hash: None,
+            });
+        }
+    }
+    let rust_toolchain = run_configuration.effective_rust_toolchain(project);
+    let toolchain_env = rust_toolchain.map(|t| ("RUSTUP_TOOLCHAIN", t));
+
+    let mut prepare_env = Vec::new();
+    if let Some(prepare_script) = &project.prepare_script {
+        let prepare_env_path = workdir.join(format!("{}.prepare-env", project.name));
+        // Left by an earlier attempt under --reclone-on-failure
+        if prepare_env_path.exists() {
+            fs::remove_file(&prepare_env_path)?;
+        }
+        log_command(
+            ctx.in_container(
+                project,
+                cmd!(sh, "/bin/bash -c {prepare_script}")
+                    .env("VERITA_PREPARE_ENV", &prepare_env_path)
+                    .envs(toolchain_env)
+                    .into(),
+            ),
+        )
+        .status()
+        .map_err(|e| anyhow!("cannot execute prepare script for {}: {}", &project.name, e))?;
+        if prepare_env_path.exists() {
+            prepare_env = read_env_file(&prepare_env_path)?;
+        }
+    }
+    // Verus runs from the project's workdir, which may only exist once prepared
+    let verus_dir = match &project.workdir {
+        Some(d) => repo_path.join(d),
+        None => repo_path.clone(),
+    };
+    if !verus_dir.is_dir() {
+        return Err(anyhow!(
+            "workdir {} of project {} does not exist",
+            verus_dir.display(),
+            project.name
+        ));
+    }
+    sh.change_dir(&verus_dir);
+    if verus_dir.join(&project.crate_root).is_dir() {
+        return Err(anyhow!(
+            "crate_root {} of project {} is a directory; Verus needs the root .rs file",
+            project.crate_root,
+            project.name
+        ));
+    }
+    // Verus is always given the root file; this only records whether it belongs to a
+    // Cargo package or is a standalone proof file
+    let crate_mode = if verus_dir.join("Cargo.toml").exists() {
+        "cargo"
+    } else {
+        "single_file"
+    };
+    let solver = project.solver.unwrap_or_default();
+    let solver_timeout_ms = run_configuration.effective_solver_timeout_ms(project);
+    let project_verification_start = std::time::Instant::now();
+    let fingerprinted_args = verus_args(args, run_configuration, project);
+    let result_fingerprint = fingerprint::ResultFingerprint {
+        project_hash: &hash,
+        workdir: project.workdir.as_deref(),
+        sparse_paths: project.sparse_paths.as_deref(),
+        prepare_script: project.prepare_script.as_deref(),
+        prepare_env: &prepare_env,
+        rust_toolchain: rust_toolchain.map(String::as_str),
+        container: run_configuration.container.as_deref(),
+        offline: run_configuration.offline,
+        cargo_home: run_configuration.cargo_home.as_deref(),
+        verus_hash: verus_hash.as_deref(),
+        verus_binary_digest,
+        verus_args: &fingerprinted_args
+            .iter()
+            .filter(|a| !fingerprint::PRESENTATION_ARGS.contains(&a.as_str()))
+            .cloned()
+            .collect::<Vec<_>>(),
+        verus_features: &run_configuration.verus_features,
+        solver_versions,
+    }
+    .digest();
+    // Logging doesn't affect the result, and the log directory differs between runs, so it is
+    // not part of the fingerprint.  Under --seeds, each seed logs to a directory of its own
+    let smt_dir = std::path::absolute(output_path.join(&project.name).join("artifacts/smt"))?;
+    let with_smt_log = |mut verus_args: Vec<String>, dir: &Path| {
+        if args.capture_smt {
+            verus_args.extend([
+                "--log".to_string(),
+                "smt".to_string(),
+                "--log-dir".to_string(),
+                dir.display().to_string(),
+            ]);
+        }
+        verus_args
+    };
+    let verus_args = with_smt_log(fingerprinted_args.clone(), &smt_dir);
+    let timeout_seconds = run_configuration.effective_timeout_seconds(project);
+    let run_verus = |verus_args: &[String]| {
+        process::output_with_timeout(
+            log_command(
+                ctx.in_container(
+                    project,
+                    cmd!(sh, "{verus_binary_path} {verus_args...}")
+                        .envs(prepare_env.iter().map(|(k, v)| (k, v)))
+                        .envs(toolchain_env)
+                        .into(),
+                ),
+            ),
+            timeout_seconds.map(Duration::from_secs),
+            || ctx.kill_container(project),
+        )
+        .map_err(|e| anyhow!("cannot execute verus on {}: {}", &project.name, e))
+    };
+    // Retry failures up to the project's `retries`, and solver OOMs up to
+    // `solver_oom_retries`, within the run's shared budget.  Timeouts are not retried: a
+    // proof that never terminates would only time out again
+    let mut attempt_start = project_verification_start;
+    let mut retries = 0;
+    let mut oom_retries = 0;
+    let (output, timed_out) = loop {
+        let (output, timed_out) = run_verus(&verus_args)?;
+        let oom = !output.status.success() && looks_like_solver_oom(&output.stderr);
+        let may_retry = if oom {
+            oom_retries < run_configuration.solver_oom_retries
+        } else {
+            retries < project.retries
+        };
+        if output.status.success() || timed_out || !may_retry {
+            break (output, timed_out);
+        }
+        // Take a retry from the budget, which concurrent projects share
+        let budgeted = ctx
+            .total_retries
+            .fetch_update(
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                |n| match run_configuration.max_total_retries {
+                    Some(m) if n >= m => None,
+                    _ => Some(n + 1),
+                },
+            );
+        if let Err(spent) = budgeted {
+            if !ctx.retry_budget_exhausted.swap(true, Ordering::SeqCst) {
+                warn!(
+                    "retry budget of {} exhausted; failures are no longer retried",
+                    spent
+                );
+            }
+            break (output, false);
+        }
+        if oom {
+            oom_retries += 1;
+            let backoff = Duration::from_millis(
+                run_configuration.solver_oom_backoff_ms * u64::from(oom_retries),
+            );
+            warn!(
+                "\tsolver ran out of memory on {}; retrying in {}ms ({} of {})",
+                project.name,
+                backoff.as_millis(),
+                oom_retries,
+                run_configuration.solver_oom_retries
+            );
+            std::thread::sleep(backoff);
+        } else {
+            retries += 1;
+            info!(
+                "\tVerus failed on {}; retrying ({} of {})",
+                project.name, retries, project.retries
+            );
+        }
+        attempt_start = std::time::Instant::now();
+    };
+    let project_verification_duration = attempt_start.elapsed();
+    if timed_out {
+        warn!(
+            "Verus timed out on {} after {}s and was killed",
+            project.name,
+            project_verification_duration.as_secs()
+        );
+    }
+
+    // Rerun with the remaining seeds and compare each outcome with the main run's
+    let mut seed_results = Vec::new();
+    if let Some(seeds) = args.seeds {
+        let outcome = |output: &std::process::Output| {
+            let errors = serde_json::from_slice::<VerusOutput>(&output.stdout)
+                .ok()
+                .map(|o| o.total_errors());
+            (output.status.success(), errors)
+        };
+        let main_outcome = outcome(&output);
+        seed_results.push(serde_json::json!({
+            "seed": 0,
+            "success": main_outcome.0,
+            "errors": main_outcome.1,
+            "verification_duration_ms": project_verification_duration.as_millis() as u64,
+        }));
+        let main_seed = seed_args(0);
+        let seed_position = fingerprinted_args
+            .windows(main_seed.len())
+            .position(|w| w == main_seed.as_slice())
+            .expect("seed 0 is passed under --seeds");
+        for seed in 1..seeds {
+            let mut seed_verus_args = fingerprinted_args.clone();
+            seed_verus_args.splice(
+                seed_position..seed_position + main_seed.len(),
+                seed_args(seed),
+            );
+            let seed_verus_args = with_smt_log(
+                seed_verus_args,
+                &smt_dir.with_file_name(format!("smt-seed-{}", seed)),
+            );
+            let seed_start = std::time::Instant::now();
+            let (seed_output, _) = run_verus(&seed_verus_args)?;
+            let seed_outcome = outcome(&seed_output);
+            if seed_outcome != main_outcome {
+                warn!(
+                    "{} is unstable: seed {} gave success={} errors={:?}, seed 0 gave success={} errors={:?}",
+                    project.name,
+                    seed,
+                    seed_outcome.0,
+                    seed_outcome.1,
+                    main_outcome.0,
+                    main_outcome.1
+                );
+            }
+            seed_results.push(serde_json::json!({
+                "seed": seed,
+                "success": seed_outcome.0,
+                "errors": seed_outcome.1,
+                "verification_duration_ms": seed_start.elapsed().as_millis() as u64,
+            }));
+        }
+    }
+    let seed_stable = args.seeds.map(|_| {
+        seed_results
+            .windows(2)
+            .all(|w| w[0]["success"] == w[1]["success"] && w[0]["errors"] == w[1]["errors"])
+    });
+    let project_output_path_json = output_path.join(&project.name).with_extension("json");
+    let smt_hash = if args.capture_smt {
+        artifacts::smt_digest(&smt_dir)?
+    } else {
+        None
+    };
+    let smt_changed = smt_hash
+        .as_deref()
+        .and_then(|h| baseline.as_ref()?.smt_changed(&project.name, h));
+    if smt_changed == Some(true) {
+        info!(
+            "\tthe SMT queries of {} changed since the baseline",
+            project.name
+        );
+    }
+    let collected_artifacts = match &project.collect_artifacts {
+        Some(patterns) => {
+            let crate_dir = verus_dir.join(&project.crate_root);
+            let crate_dir = if crate_dir.is_dir() {
+                crate_dir.as_path()
+            } else {
+                crate_dir.parent().unwrap_or(&verus_dir)
+            };
+            artifacts::collect_artifacts(
+                crate_dir,
+                patterns,
+                &output_path.join(&project.name).join("artifacts"),
+            )?
+        }
+        None => Vec::new(),
+    };
+
+    let (mut output_json, verus_output) =
+        match serde_json::from_slice::<serde_json::Value>(&output.stdout) {
+            Ok(mut output_json) => {
+                let verus_output: Option<VerusOutput> =
+                    match serde_json::from_value::<VerusOutput>(output_json.clone()) {
+                        Ok(v) => {
+                            for r in v.crate_results() {
+                                info!(
+                                    "\t{}: {} verified, {} errors, {} warnings{}",
+                                    r.crate_name().unwrap_or(&project.name),
+                                    r.verified().unwrap_or(0),
+                                    r.errors().unwrap_or(0),
+                                    r.warnings().unwrap_or(0),
+                                    if r.is_verifying_entire_crate() == Some(false) {
+                                        " (scoped: not the entire crate)"
+                                    } else {
+                                        ""
+                                    }
+                                );
+                            }
+                            Some(v)
+                        }
+                        Err(e) => {
+                            error!(
+                                "cannot parse verus json output for {}: {}",
+                                &project.name, e
+                            );
+                            error!("got: {:?}", output_json);
+                            None
+                        }
+                    };
+                let verus_profile = match verus_output.as_ref().filter(|_| args.profile_verus) {
+                    Some(v) => match v.folded_smt_profile() {
+                        Some(profile) => {
+                            let artifacts_dir = output_path.join(&project.name).join("artifacts");
+                            fs::create_dir_all(&artifacts_dir)?;
+                            let path = artifacts_dir.join("verus-profile.folded");
+                            fs::write(&path, profile)?;
+                            Some(path)
+                        }
+                        None => {
+                            warn!("verus reported no timing breakdown for {}", project.name);
+                            None
+                        }
+                    },
+                    None => None,
+                };
+                output_json["runner"] = serde_json::json!({
+                    "success": output.status.success(),
+                    "stderr": String::from_utf8_lossy(&output.stderr),
+                    "verus_git_url": run_configuration.verus_git_url,
+                    "verus_refspec": run_configuration.verus_refspec,
+                    "verus_hash": verus_hash,
+                    "verus_features": run_configuration.verus_features,
+                    "verus_verify_vstd": run_configuration.verus_verify_vstd,
+                    "verus_binary": verus_binary_path,
+                    "verus_binary_mtime": verus_binary_mtime,
+                    "run_configuration": project,
+                    "verus_args": verus_args,
+                    "retries": retries,
+                    "solver_oom_retries": oom_retries,
+                    "refspec": refspec,
+                    "workdir": project.workdir.as_deref().unwrap_or("."),
+                    "sparse_checkout": sparse_checkout,
+                    "shallow_clone": shallow_clone,
+                    "cloned_from_cache": cloned_from_cache,
+                "recloned": recloned,
+                    "container": run_configuration.container,
+                    "solver_matrix": project.matrix_entry.as_ref().map(|(project, entry)| {
+                        serde_json::json!({"project": project, "entry": entry})
+                    }),
+                    "crate_mode": crate_mode,
+                    "tags": project.tags,
+                    "offline": run_configuration.offline,
+                    "rust_toolchain": rust_toolchain,
+                    // Only the names: values may be secrets
+                    "prepare_env": prepare_env.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+                    "report_long_running": run_configuration.report_long_running,
+                    "verification_duration_ms": project_verification_duration.as_millis() as u64,
+                    "z3_version": z3_version,
+                    "cvc5_version": cvc5_version,
+                    "solver_versions": solver_versions,
+                    "solver": solver,
+                    "solver_timeout_ms": solver_timeout_ms,
+                    // Scoped results only cover part of the crate, e.g., `verify_modules`
+                    "scoped_verification": project.verify_modules.is_some()
+                        || verus_output.as_ref().is_some_and(|o| o.is_scoped()),
+                    "is_verifying_entire_crate": verus_output.as_ref().map(|o| {
+                        o.crate_results()
+                            .iter()
+                            .map(|r| r.is_verifying_entire_crate())
+                            .collect::<Vec<_>>()
+                    }),
+                    "verify_modules": project.verify_modules,
+                    "artifacts": collected_artifacts,
+                    "verus_profile": verus_profile,
+                    "smt_hash": smt_hash,
+                    "smt_changed": smt_changed,
+                    "pass_ratio": verus_output.as_ref().and_then(|o| o.pass_ratio()),
+                    "functions_per_second": verus_output
+                        .as_ref()
+                        .and_then(|o| o.functions_per_second()),
+                    "smt_function_count": verus_output
+                        .as_ref()
+                        .and_then(|o| o.smt_function_count()),
+                    "host": host,
+                    "label": args.label,
+                    "description": run_configuration.description,
+                    "shuffle_seed": args.shuffle,
+                    "date": date,
+                    "result_fingerprint": result_fingerprint,
+                    "clone_size_bytes": clone_size_bytes,
+                    "revision_change": revision_change,
+                    "seeds": seed_results,
+                    "seed_stable": seed_stable,
+                });
+                (output_json, verus_output)
+            }
+            Err(e) => {
+                error!("cannot parse verus output for {}: {}", &project.name, e);
+                error!("got: {}", String::from_utf8_lossy(&output.stdout));
+                (
+                    serde_json::json!({
+                        "runner": {
+                            "success": output.status.success(),
+                            "stderr": String::from_utf8_lossy(&output.stderr),
+                            "invalid_output_json": true,
+                        }
+                    }),
+                    None,
+                )
+            }
+        };
+    let has_warnings = verus_output
+        .as_ref()
+        .is_some_and(|o| o.total_warnings() > 0);
+    let is_scoped = verus_output.as_ref().is_some_and(|o| o.is_scoped());
+    let verified = verus_output
+        .as_ref()
+        .is_some_and(|o| o.verification_succeeded());
+    let vir_error = verus_output
+        .as_ref()
+        .is_some_and(|o| o.encountered_vir_error());
+    let status = if timed_out {
+        ProjectStatus::TimedOut
+    } else if args.vir_errors_as_infra && vir_error {
+        ProjectStatus::InfrastructureError
+    } else if project.compile && !output.status.success() && verified {
+        ProjectStatus::CompileFailed
+    } else if !output.status.success() || (args.warnings_as_errors && has_warnings) {
+        ProjectStatus::Failure
+    } else if is_scoped {
+        ProjectStatus::PartialSuccess
+    } else {
+        ProjectStatus::Success
+    };
+    let status = match status {
+        ProjectStatus::Failure | ProjectStatus::CompileFailed | ProjectStatus::TimedOut
+            if project.allow_failure =>
+        {
+            ProjectStatus::AllowedFailure
+        }
+        ProjectStatus::Success | ProjectStatus::PartialSuccess if project.allow_failure => {
+            warn!(
+                "{} is marked allow_failure but passed; consider removing the marker",
+                project.name
+            );
+            status
+        }
+        status => status,
+    };
+    output_json["runner"]["status"] = serde_json::json!(status);
+    output_json["runner"]["timed_out"] = serde_json::json!(timed_out);
+    output_json["runner"]["timeout_seconds"] = serde_json::json!(timeout_seconds);
+    // Also set when the output was unparseable, as it usually is after a kill
+    output_json["runner"]["verification_duration_ms"] =
+        serde_json::json!(project_verification_duration.as_millis() as u64);
+    if project.compile {
+        // Unknown when verification itself failed
+        output_json["runner"]["compile_success"] =
+            serde_json::json!(verified.then_some(status != ProjectStatus::CompileFailed));
+    }
+    ctx.write_project_json(project, &project_output_path_json, &output_json)?;
+
+    let (status_name, hook) = match status {
+        ProjectStatus::Success => ("success", &project.on_success_script),
+        ProjectStatus::PartialSuccess => ("partial_success", &project.on_success_script),
+        ProjectStatus::CompileFailed => ("compile_failed", &project.on_failure_script),
+        ProjectStatus::AllowedFailure => ("allowed_failure", &project.on_failure_script),
+        ProjectStatus::InfrastructureError => ("infrastructure_error", &project.on_failure_script),
+        ProjectStatus::TimedOut => ("timed_out", &project.on_failure_script),
+        _ => ("failure", &project.on_failure_script),
+    };
+    if let Some(hook) = hook {
+        let errors = verus_output
+            .as_ref()
+            .map(|o| o.total_errors())
+            .unwrap_or(0)
+            .to_string();
+        let duration_ms = project_verification_duration.as_millis().to_string();
+        let output_json_path = if args.no_per_project_files {
+            None
+        } else {
+            Some(fs::canonicalize(&project_output_path_json)?)
+        };
+        let hook_output = log_command(
+            cmd!(sh, "/bin/bash -c {hook}")
+                .env("VERITA_STATUS", status_name)
+                .env("VERITA_ERRORS", errors)
+                .env("VERITA_DURATION_MS", duration_ms)
+                .envs(output_json_path.as_ref().map(|p| ("VERITA_OUTPUT_JSON", p)))
+                .into(),
+        )
+        .output()
+        .map_err(|e| {
+            anyhow!(
+                "cannot execute {} hook for {}: {}",
+                status_name,
+                &project.name,
+                e
+            )
+        })?;
+        info!(
+            "{} hook for {} exited with {}\nstdout:\n{}\nstderr:\n{}",
+            status_name,
+            project.name,
+            hook_output.status,
+            String::from_utf8_lossy(&hook_output.stdout),
+            String::from_utf8_lossy(&hook_output.stderr)
+        );
+    }
+
+    // A failure may be flaky, so only a success makes the commit skippable
+    if matches!(
+        status,
+        ProjectStatus::Success | ProjectStatus::PartialSuccess
+    ) {
+        ctx.revisions
+            .lock()
+            .unwrap()
+            .record(&revision_state_key(ctx, project), &hash)?;
+    }
+
+    Ok(ProjectSummary {
+        project: project.clone(),
+        status,
+        hash,
+        verification_duration: project_verification_duration,
+        verus_output,
+        result_fingerprint: Some(result_fingerprint),
+        seed_stable,
+        clone_size_bytes,
+        revision_change,
+        smt_hash,
+    })
+}
+
+/// Record that `project` failed with `e` at `stage` (`clone`, or `run` for anything after
+/// cloning) as an infrastructure error, so that the other projects can still run
+fn failed_project(
+    project: &RunConfigurationProject,
+    ctx: &RunContext,
+    stage: &str,
+    e: &anyhow::Error,
+) -> anyhow::Result<ProjectSummary> {
+    error!("{} of {} failed: {:#}", stage, project.name, e);
+    ctx.write_project_json(
+        project,
+        &ctx.output_path.join(&project.name).with_extension("json"),
+        &serde_json::json!({
+            "runner": {
+                "failed_stage": stage,
+                "error": format!("{:#}", e),
+                "status": ProjectStatus::InfrastructureError,
+                "run_configuration": project,
+                "label": ctx.args.label,
+                "date": ctx.date,
+            }
+        }),
+    )?;
+    Ok(ProjectSummary {
+        project: project.clone(),
+        status: ProjectStatus::InfrastructureError,
+        hash: String::new(),
+        verification_duration: Duration::ZERO,
+        verus_output: None,
+        result_fingerprint: None,
+        seed_stable: None,
+        clone_size_bytes: None,
+        revision_change: None,
+        smt_hash: None,
+    })
+}
+
+/// Record that `project` was not started because the `--max-runtime` budget was used up
+fn not_run_project(
+    project: &RunConfigurationProject,
+    ctx: &RunContext,
+) -> anyhow::Result<ProjectSummary> {
+    warn!(
+        "not running {}: time budget of {}s exceeded",
+        project.name,
+        ctx.args.max_runtime.unwrap_or_default()
+    );
+    ctx.write_project_json(
+        project,
+        &ctx.output_path.join(&project.name).with_extension("json"),
+        &serde_json::json!({
+            "runner": {
+                "not_run": "time budget exceeded",
+                "status": ProjectStatus::NotRun,
+                "run_configuration": project,
+                "label": ctx.args.label,
+                "date": ctx.date,
+            }
+        }),
+    )?;
+    Ok(ProjectSummary {
+        project: project.clone(),
+        status: ProjectStatus::NotRun,
+        hash: String::new(),
+        verification_duration: Duration::ZERO,
+        verus_output: None,
+        result_fingerprint: None,
+        seed_stable: None,
+        clone_size_bytes: None,
+        revision_change: None,
+        smt_hash: None,
+    })
+}
+
+/// The Verus binary to run (`--verus-binary`, or the release build in the Verus repository)
+/// and its modification time, after checking that it exists and is executable
+pub(crate) fn find_verus_binary(
+    args: &Args,
+    verus_repo: &Path,
+) -> anyhow::Result<(PathBuf, std::time::SystemTime)> {
+    let verus_binary_path = match &args.verus_binary {
+        Some(p) => fs::canonicalize(p)
+            .map_err(|e| anyhow!("failed to find verus binary {}: {}", p.display(), e))?,
+        None => verus_repo.join("source/target-verus/release/verus"),
+    };
+    let verus_binary_mtime = match fs::metadata(&verus_binary_path) {
+        Err(_) => {
+            return Err(anyhow!(
+                "failed to find verus binary: {}",
+                verus_binary_path.display()
+            ));
+        }
+        Ok(m) if !m.is_file() || m.permissions().mode() & 0o111 == 0 => {
+            return Err(anyhow!(
+                "verus binary is not executable: {}",
+                verus_binary_path.display()
+            ));
+        }
+        Ok(m) => m.modified()?,
+    };
+    Ok((verus_binary_path, verus_binary_mtime))
+}
+
+/// Augments or filters each project's output JSON before it is written (see
+/// [`run_with_output_transform`])
+pub type OutputTransform =
+    Box<dyn Fn(&RunConfigurationProject, &mut serde_json::Value) + Send + Sync>;
+
+/// Clone, prepare, and verify each project in the configuration, writing per-project output
+/// JSON under `args.output_dir`
+pub fn run(args: &Args) -> anyhow::Result<RunSummary> {
+    run_with_output_transform(args, None)
+}
+
+/// What a run would do for one project, from [`dry_run`]
+pub struct PlannedProject {
+    pub name: String,
+    pub git_url: String,
+    pub refspec: String,
+    /// Why the refspec looks unresolvable, if it does
+    pub refspec_problem: Option<String>,
+    /// The Verus command, run in the clone's `workdir` after the `prepare_script`
+    pub verus_command: String,
+}
+
+impl std::fmt::Display for PlannedProject {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{}: {} at {}", self.name, self.git_url, self.refspec)?;
+        if let Some(problem) = &self.refspec_problem {
+            writeln!(f, "  FAIL: {}", problem)?;
+        }
+        write!(f, "  $ {}", self.verus_command)
+    }
+}
+
+/// Plan the run `args` describes: load and validate the configuration, select the projects,
+/// and check (without cloning) that each refspec is reachable
+pub fn dry_run(args: &Args) -> anyhow::Result<Vec<PlannedProject>> {
+    let run_configuration = load_run_configuration(args)?.expand_solver_matrices();
+    let verus_binary_path = match std::fs::canonicalize(&args.verus_repo)
+        .map_err(anyhow::Error::from)
+        .and_then(|repo| find_verus_binary(args, &repo))
+    {
+        Ok((path, _)) => path,
+        Err(e) => {
+            warn!("{}", e);
+            PathBuf::from("verus")
+        }
+    };
+    Ok(select_projects(args, &run_configuration)?
+        .into_iter()
+        .map(|project| {
+            let refspec = run_configuration
+                .effective_refspec(project)
+                .expect("validated refspec");
+            let mut verus_command = std::process::Command::new(&verus_binary_path);
+            verus_command.args(verus_args(args, &run_configuration, project));
+            PlannedProject {
+                name: project.name.clone(),
+                git_url: project.git_url.clone(),
+                refspec: refspec.to_string(),
+                refspec_problem: preflight::check_refspec_reachable(&project.git_url, refspec)
+                    .err()
+                    .map(|e| e.to_string()),
+                verus_command: shell_command_line(&verus_command),
+            }
+        })
+        .collect())
+}
+
+/// The projects of `run_configuration` that `args` selects (by tag, `--selection`, and
+/// `--only-failed`), in the order they run
+fn select_projects<'a>(
+    args: &Args,
+    run_configuration: &'a RunConfiguration,
+) -> anyhow::Result<Vec<&'a RunConfigurationProject>> {
+    for tag in args.tag.iter().chain(&args.exclude_tag) {
+        if !run_configuration
+            .projects
+            .iter()
+            .any(|p| p.tags.contains(tag))
+        {
+            warn!("no project has tag {}", tag);
+        }
+    }
+    let selection = args
+        .selection
+        .as_deref()
+        .map(|path| read_selection(path, run_configuration))
+        .transpose()?;
+    let previous = match &args.only_failed {
+        Some(dir) if args.only_status.is_empty() => Some(read_previous_statuses(
+            dir,
+            &[
+                ProjectStatus::Failure,
+                ProjectStatus::PartialSuccess,
+                ProjectStatus::CompileFailed,
+                ProjectStatus::TimedOut,
+            ],
+            run_configuration,
+        )?),
+        Some(dir) => Some(read_previous_statuses(
+            dir,
+            &args.only_status,
+            run_configuration,
+        )?),
+        None => None,
+    };
+    let available = || {
+        run_configuration
+            .projects
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if let Some(unknown) = args
+        .projects
+        .iter()
+        .find(|n| !run_configuration.projects.iter().any(|p| &&p.name == n))
+    {
+        return Err(anyhow!(
+            "--project names unknown project {}; the projects are: {}",
+            unknown,
+            available()
+        ));
+    }
+    let filter = args
+        .filter
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| anyhow!("invalid --filter: {}", e))?;
+    let mut projects: Vec<_> = run_configuration
+        .ordered_projects()
+        .into_iter()
+        .filter(|p| p.matches_tags(&args.tag, &args.exclude_tag))
+        .filter(|p| selection.as_ref().is_none_or(|s| s.contains(&p.name)))
+        .filter(|p| previous.as_ref().is_none_or(|s| s.contains(&p.name)))
+        .filter(|p| args.projects.is_empty() || args.projects.contains(&p.name))
+        .filter(|p| filter.as_ref().is_none_or(|f| f.is_match(&p.name)))
+        .collect();
+    if let Some(seed) = args.shuffle {
+        shuffle(&mut projects, seed);
+        info!(
+            "shuffled project order (seed {}): {}",
+            seed,
+            projects
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if projects.is_empty() {
+        let msg = if run_configuration.projects.is_empty() {
+            "no projects will run: the configuration has no projects".to_string()
+        } else {
+            format!(
+                "no projects will run: the tag filters, --selection, --only-failed, --project, \
+                 and --filter excluded every project (the projects are: {})",
+                available()
+            )
+        };
+        if args.error_on_empty {
+            return Err(anyhow!(msg));
+        }
+        warn!("{}", msg);
+    }
+    Ok(projects)
+}
+
+/// [`run`], passing each project's output JSON through `output_transform` just before it is
+/// written, after the `runner` block has been assembled and before `--redact` is applied
+pub fn run_with_output_transform(
+    args: &Args,
+    output_transform: Option<OutputTransform>,
+) -> anyhow::Result<RunSummary> {
+    let run_start = std::time::Instant::now();
+    let verus_repo = std::fs::canonicalize(&args.verus_repo)?;
+    // Rather than find out once every project has run
+    if args.sqlite.is_some() {
+        output::SqliteSink::check_available()?;
+    }
+
+    // let verus_repo = Repository::open(args.verus_repo)?;
+    // println!("Found repo with head {:?}, state {:?}, ", verus_repo.head()?.name().unwrap(), verus_repo.state());
+    let verus_head = Repository::open(&verus_repo)
+        .and_then(|r| {
+            let commit = r.head()?.peel_to_commit()?;
+            Ok((commit.id().to_string(), commit.time().seconds()))
+        })
+        .ok();
+    let verus_hash = verus_head.as_ref().map(|(hash, _)| hash.clone());
+
+    let (verus_binary_path, verus_binary_mtime) = find_verus_binary(args, &verus_repo)?;
+    info!("Found verus binary: {}", verus_binary_path.display());
+    if let Some(reason) = preflight::binary_staleness(
+        verus_binary_mtime,
+        verus_head
+            .map(|(_, seconds)| std::time::UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)),
+        args.max_binary_age
+            .map(|hours| Duration::from_secs(hours * 3600)),
+        std::time::SystemTime::now(),
+    ) {
+        if args.fail_on_stale_binary {
+            return Err(anyhow!("{}", reason));
+        }
+        warn!("{}", reason);
+    }
+    let verus_binary_mtime = chrono::DateTime::<chrono::Utc>::from(verus_binary_mtime).to_rfc3339();
+    let verus_binary_digest = fingerprint::file_digest(&verus_binary_path)
+        .map_err(|e| anyhow!("cannot read {}: {}", verus_binary_path.display(), e))?;
+
+    let run_configuration = load_run_configuration(args)?.expand_solver_matrices();
+    let projects = select_projects(args, &run_configuration)?;
+
+    let solver_versions: BTreeMap<String, String> = run_configuration
+        .all_solver_probes()
+        .into_iter()
+        .map(|probe| {
+            let v = match get_solver_version(&verus_repo, &probe.exe, &probe.version_regex) {
+                Ok(v) => v,
+                Err(_) => "unknown".to_string(),
+            };
+            (probe.exe, v)
+        })
+        .collect();
+    for (solver, expected) in [
+        ("z3", &run_configuration.expected_z3_version),
+        ("cvc5", &run_configuration.expected_cvc5_version),
+    ] {
+        let Some(expected) = expected else {
+            continue;
+        };
+        let actual = solver_versions
+            .get(solver)
+            .map_or("unknown", |v| v.as_str());
+        if !solver_version_matches(expected, actual) {
+            let msg = format!(
+                "expected {} version {}, but found {}",
+                solver, expected, actual
+            );
+            if !args.lenient_solver_versions {
+                return Err(anyhow!("{} (see --lenient-solver-versions)", msg));
+            }
+            warn!("{}", msg);
+        }
+    }
+
+    info!("Loaded run configuration:");
+    dbg!(&run_configuration);
+
+    info!("Running projects");
+    let mut shell_env: Vec<(String, std::ffi::OsString)> = Vec::new();
+    // Minimal Verus builds may ship only some solvers; don't point Verus at missing binaries
+    for (var, solver) in [("VERUS_Z3_PATH", "z3"), ("VERUS_CVC5_PATH", "cvc5")] {
+        let solver_path = verus_repo.join("source").join(solver);
+        if solver_path.exists() {
+            shell_env.push((var.to_string(), solver_path.into()));
+        } else {
+            warn!(
+                "{} not found at {}; not setting {}",
+                solver,
+                solver_path.display(),
+                var
+            );
+        }
+    }
+
+    // If the Singular option is provided, confirm the binary exists and set the environment variable
+    if let Some(p) = &args.singular {
+        if fs::metadata(p).is_err() {
+            return Err(anyhow!(
+                "failed to find specified Singular binary: {}",
+                p.display()
+            ));
+        }
+        shell_env.push(("VERUS_SINGULAR_PATH".to_string(), p.into()));
+    }
+
+    if run_configuration.offline {
+        shell_env.push(("CARGO_NET_OFFLINE".to_string(), "true".into()));
+    }
+    if let Some(cargo_home) = &run_configuration.cargo_home {
+        // Absolute, since the shell changes into each project's clone
+        let cargo_home = fs::canonicalize(cargo_home)
+            .ok()
+            .filter(|p| p.is_dir())
+            .ok_or_else(|| anyhow!("cargo_home is not a directory: {}", cargo_home.display()))?;
+        shell_env.push(("CARGO_HOME".to_string(), cargo_home.into()));
+    }
+
+    let date = format_run_date(&args.timestamp_format, args.local_time)?;
+    let output_path = args.output_dir.join(format!("{}-{}", &date, &args.label));
+    let tmp_dir = cleanup::ScratchDir::new(TempDir::new("verita")?.into_path());
+    let perm_temp_dir = std::env::temp_dir().join("verita").join(&date);
+    std::fs::create_dir_all(&output_path)?;
+    let workdir = if args.debug_level > 0 {
+        // Use a directory that won't disappear after we run, so we can debug any issues that arise
+        perm_temp_dir.as_path()
+    } else {
+        // Use a directory that will be automatically reclaimed after we terminate
+        tmp_dir.path()
+    };
+    if !args.no_preflight {
+        let required_gb = projects
+            .iter()
+            .map(|p| {
+                p.estimated_clone_gb
+                    .unwrap_or(preflight::DEFAULT_ESTIMATED_CLONE_GB)
+            })
+            .sum();
+        preflight::check_disk_space(workdir, required_gb)?;
+        if let Some(project) = projects.first() {
+            preflight::check_reachable(&project.git_url)?;
+        }
+    }
+    let mut stream = args.stream.as_deref().map(open_sink).transpose()?;
+    let mut events = args.events.as_deref().map(open_sink).transpose()?;
+    let baseline = args
+        .baseline
+        .as_deref()
+        .map(baseline::Baseline::load)
+        .transpose()?;
+    // Kept with the clones they describe, when there is a clone cache
+    let revisions_dir = args.cache_dir.as_ref().unwrap_or(&args.output_dir);
+    let revisions =
+        revisions::RevisionState::load(&revisions_dir.join(revisions::STATE_FILE_NAME))?;
+    let container = match &run_configuration.container {
+        Some(image) => {
+            let mount = |path: &Path, writable| -> anyhow::Result<container::Mount> {
+                Ok(container::Mount {
+                    path: std::path::absolute(path)?,
+                    writable,
+                })
+            };
+            let mut mounts = vec![
+                mount(&verus_repo, false)?,
+                mount(&verus_binary_path, false)?,
+                mount(workdir, true)?,
+                mount(&output_path, true)?,
+            ];
+            if let Some(singular) = &args.singular {
+                mounts.push(mount(singular, false)?);
+            }
+            if let Some(cargo_home) = &run_configuration.cargo_home {
+                mounts.push(mount(cargo_home, true)?);
+            }
+            info!(
+                "running prepare scripts and verus in {} containers from {}",
+                run_configuration.container_runtime, image
+            );
+            // The output directory was just created, so it has this process's owner
+            let owner = fs::metadata(&output_path)?;
+            Some(container::Container {
+                runtime: run_configuration.container_runtime.clone(),
+                image: image.clone(),
+                mounts,
+                user: Some(format!("{}:{}", owner.uid(), owner.gid())),
+            })
+        }
+        None => None,
+    };
+    let ctx = RunContext {
+        args,
+        run_configuration: &run_configuration,
+        shell_env,
+        verus_binary_path,
+        verus_binary_mtime,
+        verus_binary_digest,
+        verus_hash: verus_hash.clone(),
+        solver_versions,
+        date: date.clone(),
+        output_path: output_path.clone(),
+        workdir: workdir.to_path_buf(),
+        baseline,
+        host: host::HostInfo::collect(),
+        container,
+        output_transform,
+        revisions: Mutex::new(revisions),
+        total_retries: AtomicU32::new(0),
+        retry_budget_exhausted: AtomicBool::new(false),
+        clone_cache_locks: Mutex::new(BTreeMap::new()),
+    };
+    let mut progress = progress::Progress::new(projects.len(), args.quiet);
+    // Workers take projects in order and report back here, where the shared outputs (the
+    // progress line, events, stream, and summary) are written
+    enum Message {
+        Started { index: usize, runs: bool },
+        Finished(usize, Box<anyhow::Result<ProjectSummary>>),
+    }
+    let next_project = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let mut results: Vec<Option<ProjectSummary>> = projects.iter().map(|_| None).collect();
+    // In completion order, for the partial summary
+    let mut finished = Vec::new();
+    std::thread::scope(|scope| -> anyhow::Result<()> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for _ in 0..args.jobs.get().min(projects.len()) {
+            let sender = sender.clone();
+            let (ctx, projects, next_project, stop) = (&ctx, &projects, &next_project, &stop);
+            scope.spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    let index = next_project.fetch_add(1, Ordering::SeqCst);
+                    let Some(project) = projects.get(index) else {
+                        break;
+                    };
+                    let runs = args
+                        .max_runtime
+                        .is_none_or(|m| run_start.elapsed().as_secs() < m);
+                    let _ = sender.send(Message::Started { index, runs });
+                    let result = if runs {
+                        run_project(project, ctx)
+                            .or_else(|e| failed_project(project, ctx, "run", &e))
+                    } else {
+                        not_run_project(project, ctx)
+                    };
+                    if sender
+                        .send(Message::Finished(index, Box::new(result)))
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for message in receiver {
+            match message {
+                Message::Started { index, runs } => {
+                    let project = projects[index];
+                    progress.update(index, &project.name);
+                    if let (true, Some(events)) = (runs, &mut events) {
+                        write_event(events, &project.name, "project_started", None)?;
+                    }
+                }
+                Message::Finished(index, result) => {
+                    progress.finished(index);
+                    // Only when even the failure could not be recorded
+                    let project_summary = (*result).inspect_err(|_| {
+                        // Let running projects finish, but start no more
+                        stop.store(true, Ordering::SeqCst)
+                    })?;
+                    let project = projects[index];
+                    if project_summary.status != ProjectStatus::NotRun {
+                        if args.explain {
+                            info!(
+                                target: SUMMARY_LOG_TARGET,
+                                "{}: {}",
+                                project.name,
+                                project_summary.explanation()
+                            );
+                        }
+                        if let Some(stream) = &mut stream {
+                            writeln!(stream, "{}", project_summary.stream_json())?;
+                            stream.flush()?;
+                        }
+                        if let Some(events) = &mut events {
+                            write_event(
+                                events,
+                                &project.name,
+                                "project_finished",
+                                Some(project_summary.status),
+                            )?;
+                        }
+                    }
+                    // Keep a usable partial summary in case the run dies before finishing
+                    finished.push(index);
+                    results[index] = Some(project_summary);
+                    let so_far: Vec<_> = finished
+                        .iter()
+                        .filter_map(|&i| results[i].as_ref())
+                        .collect();
+                    let summary_json = summary_json(&ctx, &so_far, run_start.elapsed(), false);
+                    write_json_atomically(&output_path.join("summary.json"), &summary_json)?;
+                    if let Some(summary_json_path) = &args.summary_json {
+                        write_json_atomically(summary_json_path, &summary_json)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    })?;
+    // In configuration order, however the projects were scheduled
+    let project_summaries: Vec<ProjectSummary> = results
+        .into_iter()
+        .map(|r| r.expect("every project reports a result"))
+        .collect();
+
+    progress.finish();
+
+    let count = |status| {
+        project_summaries
+            .iter()
+            .filter(|p| p.status == status)
+            .count()
+    };
+    let mut details = String::new();
+    for (n, what) in [
+        (
+            count(ProjectStatus::PartialSuccess),
+            "partially verified (scoped)",
+        ),
+        (
+            count(ProjectStatus::CompileFailed),
+            "verified but failed to compile",
+        ),
+        (count(ProjectStatus::AllowedFailure), "allowed failures"),
+        (
+            project_summaries
+                .iter()
+                .filter(|p| {
+                    p.project.allow_failure
+                        && matches!(
+                            p.status,
+                            ProjectStatus::Success | ProjectStatus::PartialSuccess
+                        )
+                })
+                .count(),
+            "unexpectedly passed despite allow_failure",
+        ),
+        (
+            count(ProjectStatus::InfrastructureError),
+            "infrastructure errors",
+        ),
+        (count(ProjectStatus::TimedOut), "timed out"),
+        (count_vir_errors(&project_summaries), "with VIR errors"),
+        (count(ProjectStatus::Unchanged), "unchanged"),
+        (
+            count(ProjectStatus::NotRun),
+            "not run (time budget exceeded)",
+        ),
+        (
+            count_unparseable(&project_summaries),
+            "with unparseable output",
+        ),
+        (
+            project_summaries
+                .iter()
+                .filter(|p| p.seed_stable == Some(false))
+                .count(),
+            "unstable across seeds",
+        ),
+    ] {
+        if n > 0 {
+            details.push_str(&format!(", {} {}", n, what));
+        }
+    }
+    if let Some(baseline) = &ctx.baseline {
+        let regressed = project_summaries
+            .iter()
+            .filter(|p| baseline.newly_regressed(&p.project.name, p.status))
+            .count();
+        details.push_str(&format!(", {} newly regressed", regressed));
+    }
+    if let Some(ratio) = suite_pass_ratio(&project_summaries) {
+        details.push_str(&format!(", {:.1}% of functions verified", ratio * 100.0));
+        if let Some(change) = ctx
+            .baseline
+            .as_ref()
+            .and_then(|b| b.suite_pass_ratio_change(ratio))
+        {
+            details.push_str(&format!(" ({} since the baseline)", change));
+        }
+    }
+    info!(
+        target: SUMMARY_LOG_TARGET,
+        "run complete: {}/{} verified, {} failed{}, total {}s",
+        count(ProjectStatus::Success),
+        project_summaries.len(),
+        count(ProjectStatus::Failure),
+        details,
+        run_start.elapsed().as_secs()
+    );
+    let clone_sizes: Vec<u64> = project_summaries
+        .iter()
+        .filter_map(|p| p.clone_size_bytes)
+        .collect();
+    if !clone_sizes.is_empty() {
+        info!(
+            target: SUMMARY_LOG_TARGET,
+            "total clone size: {} MiB across {} projects",
+            clone_sizes.iter().sum::<u64>() / (1024 * 1024),
+            clone_sizes.len()
+        );
+    }
+
+    let moved: Vec<String> = project_summaries
+        .iter()
+        .filter_map(|p| {
+            let change = p.revision_change.as_deref()?;
+            (change != "unchanged").then(|| format!("{} ({})", p.project.name, change))
+        })
+        .collect();
+    if !moved.is_empty() {
+        info!(
+            target: SUMMARY_LOG_TARGET,
+            "moved since the baseline: {}",
+            moved.join(", ")
+        );
+    }
+    if let Some(baseline) = &ctx.baseline {
+        let function_counts: Vec<String> = project_summaries
+            .iter()
+            .filter_map(|p| {
+                let count = p.verus_output.as_ref()?.smt_function_count()?;
+                let change = baseline.smt_function_count_change(&p.project.name, count)?;
+                Some(format!("{} ({})", p.project.name, change))
+            })
+            .collect();
+        if !function_counts.is_empty() {
+            info!(
+                target: SUMMARY_LOG_TARGET,
+                "SMT function counts changed since the baseline: {}",
+                function_counts.join(", ")
+            );
+        }
+        let pass_ratios: Vec<String> = project_summaries
+            .iter()
+            .filter_map(|p| {
+                let ratio = p.verus_output.as_ref()?.pass_ratio()?;
+                let change = baseline.pass_ratio_change(&p.project.name, ratio)?;
+                Some(format!("{} ({})", p.project.name, change))
+            })
+            .collect();
+        if !pass_ratios.is_empty() {
+            info!(
+                target: SUMMARY_LOG_TARGET,
+                "pass ratios changed since the baseline: {}",
+                pass_ratios.join(", ")
+            );
+        }
+        let cpu_times: Vec<String> = project_summaries
+            .iter()
+            .filter_map(|p| {
+                let ms = p.verus_output.as_ref()?.estimated_cpu_time_ms()?;
+                let change = baseline.estimated_cpu_time_change(&p.project.name, ms)?;
+                Some(format!("{} ({})", p.project.name, change))
+            })
+            .collect();
+        if !cpu_times.is_empty() {
+            let total_ms = project_summaries
+                .iter()
+                .filter_map(|p| p.verus_output.as_ref()?.estimated_cpu_time_ms())
+                .sum::<u64>();
+            let total = baseline
+                .total_estimated_cpu_time_change(total_ms)
+                .map(|change| format!("; in total {}", change))
+                .unwrap_or_default();
+            info!(
+                target: SUMMARY_LOG_TARGET,
+                "estimated CPU time changed since the baseline: {}{}",
+                cpu_times.join(", "),
+                total
+            );
+        }
+    }
+
+    let summary_json = summary_json(
+        &ctx,
+        &project_summaries.iter().collect::<Vec<_>>(),
+        run_start.elapsed(),
+        true,
+    );
+    write_json_atomically(&output_path.join("summary.json"), &summary_json)?;
+    fs::write(
+        output_path.join("report.html"),
+        output::html_report(
+            &format!("verita run {} ({})", args.label, ctx.date),
+            &project_summaries,
+        ),
+    )?;
+    if let Some(summary_json_path) = &args.summary_json {
+        write_json_atomically(summary_json_path, &summary_json)?;
+    }
+    if let Some(sqlite_path) = &args.sqlite {
+        output::SqliteSink::new(sqlite_path).record_run(
+            &ctx.date,
+            &args.label,
+            ctx.verus_hash.as_deref(),
+            &project_summaries,
+        )?;
+    }
+
+    let archive_path = if args.archive || args.archive_only {
+        let archive_path = archive::archive_dir(&output_path)?;
+        if args.archive_only {
+            fs::remove_dir_all(&output_path)?;
+        }
+        Some(archive_path)
+    } else {
+        None
+    };
+
+    Ok(RunSummary {
+        output_path,
+        project_summaries,
+        archive_path,
+    })
+}
@@ -1,17 +1,47 @@
-use serde::{Deserialize, Serialize};
+use crate::ProjectSummary;
+use anyhow::anyhow;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-#[derive(Deserialize, Hash)]
+#[derive(Serialize, Deserialize, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub struct VerusOutputFunctionSmtTime {
+    function: String,
+    time: u64,
+    success: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub struct VerusOutputModuleSmtTimes {
+    #[serde(default)]
+    module: Option<String>,
+    #[serde(default)]
+    time: Option<u64>,
+    #[serde(default)]
+    function_breakdown: Vec<VerusOutputFunctionSmtTime>,
+}
+
+#[derive(Serialize, Deserialize, Hash)]
 #[serde(rename_all = "kebab-case")]
 pub struct VerusOutputSmtTimesMs {
     smt_init: u64,
     smt_run: u64,
     total: u64,
+    /// Only emitted when `--time-expanded` is passed, by Verus builds whose `--output-json`
+    /// has a `function-breakdown` per module; for others, this stays `None`
+    #[serde(default)]
+    smt_run_module_times: Option<Vec<VerusOutputModuleSmtTimes>>,
 }
 
-#[derive(Deserialize, Hash)]
+#[derive(Serialize, Deserialize, Hash)]
 #[serde(rename_all = "kebab-case")]
 pub struct VerusOutputTimesMs {
-    estimated_cpu_time: u64,
+    /// Not reported by older Verus versions
+    #[serde(default)]
+    estimated_cpu_time: Option<u64>,
     total: u64,
     smt: VerusOutputSmtTimesMs,
 }
@@ -19,16 +49,522 @@ pub struct VerusOutputTimesMs {
 #[derive(Debug, Serialize, Deserialize, Hash, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct VerusOutputVerificationResults {
+    /// Set for per-crate results when Verus verifies several crates
+    #[serde(default)]
+    crate_name: Option<String>,
     encountered_vir_error: bool,
     success: Option<bool>,
     verified: Option<u64>,
     errors: Option<u64>,
+    /// Not reported by older Verus versions
+    #[serde(default)]
+    warnings: Option<u64>,
+    /// `Some(false)` when Verus only verified part of the crate (e.g. `--verify-module`)
     is_verifying_entire_crate: Option<bool>,
 }
 
-#[derive(Deserialize, Hash)]
+#[derive(Serialize, Deserialize, Hash)]
 #[serde(rename_all = "kebab-case")]
 pub struct VerusOutput {
     times_ms: VerusOutputTimesMs,
-    verification_results: VerusOutputVerificationResults,
+    #[serde(deserialize_with = "deserialize_verification_results")]
+    verification_results: Vec<VerusOutputVerificationResults>,
+}
+
+/// The shapes Verus uses for `verification-results`, depending on flags and on whether it
+/// verified one crate or several
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VerificationResultsShape {
+    Single(VerusOutputVerificationResults),
+    List(Vec<VerusOutputVerificationResults>),
+    Map(BTreeMap<String, VerusOutputVerificationResults>),
+}
+
+fn deserialize_verification_results<'de, D>(
+    deserializer: D,
+) -> Result<Vec<VerusOutputVerificationResults>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match VerificationResultsShape::deserialize(deserializer)? {
+        VerificationResultsShape::Single(r) => vec![r],
+        VerificationResultsShape::List(rs) => rs,
+        VerificationResultsShape::Map(rs) => rs
+            .into_iter()
+            .map(|(name, mut r)| {
+                r.crate_name.get_or_insert(name);
+                r
+            })
+            .collect(),
+    })
+}
+
+impl VerusOutputVerificationResults {
+    pub fn crate_name(&self) -> Option<&str> {
+        self.crate_name.as_deref()
+    }
+
+    pub fn verified(&self) -> Option<u64> {
+        self.verified
+    }
+
+    pub fn errors(&self) -> Option<u64> {
+        self.errors
+    }
+
+    pub fn warnings(&self) -> Option<u64> {
+        self.warnings
+    }
+
+    pub fn is_verifying_entire_crate(&self) -> Option<bool> {
+        self.is_verifying_entire_crate
+    }
+}
+
+impl VerusOutput {
+    /// One entry per verified crate (a single, unnamed entry for single-crate output)
+    pub fn crate_results(&self) -> &[VerusOutputVerificationResults] {
+        &self.verification_results
+    }
+
+    /// Functions verified, summed across all verified crates
+    pub fn total_verified(&self) -> u64 {
+        self.verification_results
+            .iter()
+            .map(|r| r.verified.unwrap_or(0))
+            .sum()
+    }
+
+    /// Fraction of functions that verified, for partial credit on failing projects; `None`
+    /// if Verus reported no functions
+    pub fn pass_ratio(&self) -> Option<f64> {
+        pass_ratio(self.total_verified(), self.total_errors())
+    }
+
+    /// Verus's total wall-clock time, in milliseconds
+    pub fn total_ms(&self) -> u64 {
+        self.times_ms.total
+    }
+
+    /// Time spent starting the SMT solvers, in milliseconds
+    pub fn smt_init_ms(&self) -> u64 {
+        self.times_ms.smt.smt_init
+    }
+
+    /// Time spent running SMT queries, in milliseconds
+    pub fn smt_run_ms(&self) -> u64 {
+        self.times_ms.smt.smt_run
+    }
+
+    /// Total time spent in the SMT solvers, in milliseconds
+    pub fn smt_total_ms(&self) -> u64 {
+        self.times_ms.smt.total
+    }
+
+    /// Verus's estimate of the CPU time used, in milliseconds, which compares better than
+    /// wall-clock time across machines with different core counts
+    pub fn estimated_cpu_time_ms(&self) -> Option<u64> {
+        self.times_ms.estimated_cpu_time
+    }
+
+    /// Verified functions per second of Verus's total time, for comparing machines and Verus
+    /// versions; `None` if nothing verified or no time was reported
+    pub fn functions_per_second(&self) -> Option<f64> {
+        functions_per_second(self.total_verified(), self.total_ms())
+    }
+
+    /// Whether every crate verified, regardless of what happened afterwards (e.g. compilation)
+    pub fn verification_succeeded(&self) -> bool {
+        !self.encountered_vir_error()
+            && self.total_errors() == 0
+            && self
+                .verification_results
+                .iter()
+                .all(|r| r.success != Some(false))
+    }
+
+    /// Whether Verus hit an internal (VIR) error in any crate
+    pub fn encountered_vir_error(&self) -> bool {
+        self.verification_results
+            .iter()
+            .any(|r| r.encountered_vir_error)
+    }
+
+    /// Errors summed across all verified crates
+    pub fn total_errors(&self) -> u64 {
+        self.verification_results
+            .iter()
+            .map(|r| r.errors.unwrap_or(0))
+            .sum()
+    }
+
+    /// Whether any crate was only partially verified, in which case the counts do not
+    /// cover the whole crate
+    pub fn is_scoped(&self) -> bool {
+        self.verification_results
+            .iter()
+            .any(|r| r.is_verifying_entire_crate == Some(false))
+    }
+
+    /// Warnings summed across all verified crates
+    pub fn total_warnings(&self) -> u64 {
+        self.verification_results
+            .iter()
+            .map(|r| r.warnings.unwrap_or(0))
+            .sum()
+    }
+
+    /// Number of functions sent to the solver (not of SMT queries: a function can need several),
+    /// i.e., the number of `function-breakdown` entries in `smt-run-module-times`; `None` if
+    /// Verus did not report a breakdown (see [`VerusOutputSmtTimesMs`])
+    pub fn smt_function_count(&self) -> Option<u64> {
+        self.times_ms
+            .smt
+            .smt_run_module_times
+            .as_ref()
+            .map(|modules| {
+                modules
+                    .iter()
+                    .map(|m| m.function_breakdown.len() as u64)
+                    .sum()
+            })
+    }
+
+    /// The `--time-expanded` breakdown in the folded-stack format read by flamegraph tools:
+    /// one `module;function milliseconds` line per function.  `None` if Verus did not report
+    /// a breakdown
+    pub fn folded_smt_profile(&self) -> Option<String> {
+        let modules = self.times_ms.smt.smt_run_module_times.as_ref()?;
+        let mut folded = String::new();
+        for module in modules {
+            for function in module.function_breakdown.iter() {
+                folded.push_str(&format!(
+                    "{};{} {}\n",
+                    module.module.as_deref().unwrap_or("unknown"),
+                    function.function,
+                    function.time
+                ));
+            }
+        }
+        Some(folded)
+    }
+}
+
+/// Compare a Verus JSON output file against [`VerusOutput`], returning one diagnostic per
+/// field that is unexpected or fails to deserialize (empty if the file matches)
+pub fn check_output_file(path: &Path) -> anyhow::Result<Vec<String>> {
+    let mut value: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let mut diagnostics = Vec::new();
+    // `verification-results` goes through an untagged enum, which hides its fields from
+    // serde_ignored and serde_path_to_error, so it is checked on its own, as the shape it has
+    let results = value
+        .as_object_mut()
+        .and_then(|o| o.insert("verification-results".to_string(), serde_json::json!([])));
+    check_value::<VerusOutput>(value, "", &mut diagnostics);
+    type Results = VerusOutputVerificationResults;
+    let prefix = "verification-results";
+    let single = |r: &serde_json::Value| {
+        ["success", "encountered-vir-error"]
+            .iter()
+            .any(|k| r.get(k).is_some())
+    };
+    match results {
+        None => {}
+        Some(results @ serde_json::Value::Array(_)) => {
+            check_value::<Vec<Results>>(results, prefix, &mut diagnostics)
+        }
+        Some(results) if single(&results) => {
+            check_value::<Results>(results, prefix, &mut diagnostics)
+        }
+        Some(results @ serde_json::Value::Object(_)) => {
+            check_value::<BTreeMap<String, Results>>(results, prefix, &mut diagnostics)
+        }
+        Some(_) => diagnostics.push(format!("field {}: expected an object or array", prefix)),
+    }
+    Ok(diagnostics)
+}
+
+/// Add [`check_output_file`]'s diagnostics for `value` as a `T`, with paths under `prefix`
+fn check_value<T: serde::de::DeserializeOwned>(
+    value: serde_json::Value,
+    prefix: &str,
+    diagnostics: &mut Vec<String>,
+) {
+    let under_prefix = |path: String| match path.as_str() {
+        "." => prefix.to_string(),
+        _ if prefix.is_empty() || path.starts_with('[') => format!("{}{}", prefix, path),
+        _ => format!("{}.{}", prefix, path),
+    };
+    let _ = serde_ignored::deserialize(value.clone(), |field| {
+        diagnostics.push(format!(
+            "unexpected field: {}",
+            under_prefix(field.to_string())
+        ))
+    })
+    .map(|_: T| ());
+    if let Err(e) = serde_path_to_error::deserialize::<_, T>(value) {
+        let field = under_prefix(e.path().to_string());
+        diagnostics.push(format!("field {}: {}", field, e.into_inner()));
+    }
+}
+
+/// `verified / (verified + errors)`, or `None` if both are zero
+pub fn pass_ratio(verified: u64, errors: u64) -> Option<f64> {
+    (verified + errors > 0).then(|| verified as f64 / (verified + errors) as f64)
+}
+
+/// `verified / (total_ms / 1000)`, or `None` if either is zero
+pub fn functions_per_second(verified: u64, total_ms: u64) -> Option<f64> {
+    (verified > 0 && total_ms > 0).then(|| verified as f64 * 1000.0 / total_ms as f64)
+}
+
+/// Run history in a SQLite database, for querying trends across runs.  Written through the
+/// `sqlite3` command-line tool, which must be on the `PATH`, since verita does not depend on
+/// a SQLite library; values are quoted as SQL string literals (see [`Self::run_sql`])
+pub struct SqliteSink {
+    path: PathBuf,
+}
+
+/// Created on first use; keyed by the run date and, for projects, the name (not the git hash,
+/// which is empty for projects that failed before resolving one)
+const SQLITE_SCHEMA: &str = "\
+CREATE TABLE IF NOT EXISTS runs (
+    date TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    verus_hash TEXT
+);
+CREATE TABLE IF NOT EXISTS projects (
+    run_date TEXT NOT NULL REFERENCES runs(date),
+    name TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    status TEXT NOT NULL,
+    verification_duration_ms INTEGER NOT NULL,
+    verified INTEGER,
+    errors INTEGER,
+    PRIMARY KEY (run_date, name)
+);
+CREATE TABLE IF NOT EXISTS metrics (
+    run_date TEXT NOT NULL REFERENCES runs(date),
+    name TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    smt_init_ms INTEGER NOT NULL,
+    smt_run_ms INTEGER NOT NULL,
+    smt_total_ms INTEGER NOT NULL,
+    total_ms INTEGER NOT NULL,
+    estimated_cpu_time_ms INTEGER,
+    PRIMARY KEY (run_date, name)
+);
+";
+
+impl SqliteSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        SqliteSink { path: path.into() }
+    }
+
+    /// The version of the `sqlite3` program that [`Self::record_run`] runs, or why it can't
+    pub fn check_available() -> anyhow::Result<String> {
+        let output = std::process::Command::new("sqlite3")
+            .arg("-version")
+            .output()
+            .map_err(|e| anyhow!("cannot run sqlite3, which --sqlite needs: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow!("sqlite3 -version failed"));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// The SQL that creates the schema if absent and inserts one row per project (and, for
+    /// projects with parsed Verus output, per project's metrics) in a single transaction.
+    /// Recording a run date again replaces its rows
+    pub fn run_sql(
+        date: &str,
+        label: &str,
+        verus_hash: Option<&str>,
+        project_summaries: &[ProjectSummary],
+    ) -> String {
+        let text = |s: &str| format!("'{}'", s.replace('\'', "''"));
+        let opt = |n: Option<u64>| n.map_or("NULL".to_string(), |n| n.to_string());
+        let mut sql = String::from(SQLITE_SCHEMA);
+        sql.push_str("BEGIN;\n");
+        sql.push_str(&format!(
+            "INSERT OR REPLACE INTO runs VALUES ({}, {}, {});\n",
+            text(date),
+            text(label),
+            verus_hash.map_or("NULL".to_string(), text)
+        ));
+        for p in project_summaries {
+            let key = format!(
+                "{}, {}, {}",
+                text(date),
+                text(&p.project.name),
+                text(&p.hash)
+            );
+            let status = serde_json::to_value(p.status).expect("status serializes");
+            sql.push_str(&format!(
+                "INSERT OR REPLACE INTO projects VALUES ({}, {}, {}, {}, {});\n",
+                key,
+                text(status.as_str().unwrap_or_default()),
+                p.verification_duration.as_millis(),
+                opt(p.verus_output.as_ref().map(|o| o.total_verified())),
+                opt(p.verus_output.as_ref().map(|o| o.total_errors())),
+            ));
+            if let Some(o) = &p.verus_output {
+                sql.push_str(&format!(
+                    "INSERT OR REPLACE INTO metrics VALUES ({}, {}, {}, {}, {}, {});\n",
+                    key,
+                    o.smt_init_ms(),
+                    o.smt_run_ms(),
+                    o.smt_total_ms(),
+                    o.total_ms(),
+                    opt(o.estimated_cpu_time_ms()),
+                ));
+            }
+        }
+        sql.push_str("COMMIT;\n");
+        sql
+    }
+
+    /// Record a run's results (see [`Self::run_sql`])
+    pub fn record_run(
+        &self,
+        date: &str,
+        label: &str,
+        verus_hash: Option<&str>,
+        project_summaries: &[ProjectSummary],
+    ) -> anyhow::Result<()> {
+        let sql = Self::run_sql(date, label, verus_hash, project_summaries);
+        let mut child = std::process::Command::new("sqlite3")
+            .arg("-bail")
+            .arg(&self.path)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("cannot run sqlite3: {}", e))?;
+        child
+            .stdin
+            .take()
+            .expect("piped stdin")
+            .write_all(sql.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "cannot record the run in {}: {}",
+                self.path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The web page for `hash` in the repository at `git_url`, for GitHub and GitLab URLs (HTTPS or
+/// SSH); `None` for other hosts
+pub fn commit_url(git_url: &str, hash: &str) -> Option<String> {
+    let path = git_url
+        .strip_prefix("https://")
+        .or_else(|| git_url.strip_prefix("ssh://git@"))
+        .or_else(|| git_url.strip_prefix("git@"))?
+        .replacen(':', "/", 1);
+    let path = path
+        .strip_suffix(".git")
+        .unwrap_or(&path)
+        .trim_end_matches('/');
+    // Credentials in HTTPS URLs are not part of the page's address
+    let path = path.rsplit_once('@').map_or(path, |(_, p)| p);
+    if path.starts_with("github.com/") {
+        Some(format!("https://{}/commit/{}", path, hash))
+    } else if path.starts_with("gitlab.com/") {
+        Some(format!("https://{}/-/commit/{}", path, hash))
+    } else {
+        None
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_REPORT_STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+td.number { text-align: right; }
+tr.failed { background: #f8d0d0; }
+tr.timed_out { background: #fce2b0; }
+.bar { display: inline-block; height: 0.8em; }
+.smt-init { background: #9ab; }
+.smt-run { background: #468; }
+";
+
+/// A self-contained page (with inline CSS, so that it can be emailed) with a row per project:
+/// its commit, status, counts, and SMT time as a bar relative to the slowest project's.
+/// Failed rows are red and timed-out rows amber
+pub fn html_report(title: &str, project_summaries: &[ProjectSummary]) -> String {
+    let max_smt_ms = project_summaries
+        .iter()
+        .filter_map(|p| Some(p.verus_output.as_ref()?.smt_total_ms()))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut rows = String::new();
+    for p in project_summaries {
+        let status = serde_json::to_value(p.status).expect("status serializes");
+        let status = status.as_str().unwrap_or_default();
+        let class = match p.status {
+            crate::ProjectStatus::Failure
+            | crate::ProjectStatus::CompileFailed
+            | crate::ProjectStatus::InfrastructureError => " class=\"failed\"",
+            crate::ProjectStatus::TimedOut => " class=\"timed_out\"",
+            _ => "",
+        };
+        let short_hash = escape_html(&p.hash[..p.hash.len().min(12)]);
+        let hash = match commit_url(&p.project.git_url, &p.hash) {
+            Some(url) if !p.hash.is_empty() => {
+                format!("<a href=\"{}\">{}</a>", escape_html(&url), short_hash)
+            }
+            _ => short_hash,
+        };
+        let count = |n: Option<u64>| n.map_or(String::new(), |n| n.to_string());
+        let verus_output = p.verus_output.as_ref();
+        let smt = match verus_output {
+            Some(o) => {
+                let width = |ms: u64| ms as f64 * 20.0 / max_smt_ms as f64;
+                format!(
+                    "<span class=\"bar smt-init\" style=\"width: {:.2}em\"></span>\
+                     <span class=\"bar smt-run\" style=\"width: {:.2}em\"></span> {} ms",
+                    width(o.smt_init_ms()),
+                    width(o.smt_run_ms()),
+                    o.smt_total_ms()
+                )
+            }
+            None => String::new(),
+        };
+        rows.push_str(&format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{}</td><td class=\"number\">{}</td>\
+             <td class=\"number\">{}</td><td>{}</td></tr>\n",
+            class,
+            escape_html(&p.project.name),
+            hash,
+            status,
+            count(verus_output.map(|o| o.total_verified())),
+            count(verus_output.map(|o| o.total_errors())),
+            smt
+        ));
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n{HTML_REPORT_STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n<table>\n\
+         <tr><th>project</th><th>commit</th><th>status</th><th>verified</th><th>errors</th>\
+         <th>SMT time (init, run)</th></tr>\n{rows}</table>\n</body>\n</html>\n",
+        title = escape_html(title),
+    )
 }
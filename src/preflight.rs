@@ -0,0 +1,116 @@
+use anyhow::anyhow;
+use git2::{Direction, Remote};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tracing::info;
+use xshell::{cmd, Shell};
+
+/// Assumed clone size for projects that don't specify `estimated_clone_gb`
+pub const DEFAULT_ESTIMATED_CLONE_GB: u64 = 1;
+
+/// Total size of the files under `dir`, without following symlinks
+pub fn dir_size_bytes(dir: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() {
+            dir_size_bytes(&entry.path())?
+        } else {
+            metadata.len()
+        };
+    }
+    Ok(total)
+}
+
+/// Available space, in KiB, on the filesystem holding `dir` (or its nearest existing ancestor)
+fn available_kib(dir: &Path) -> anyhow::Result<u64> {
+    let dir = dir
+        .ancestors()
+        .find(|d| d.exists())
+        .ok_or_else(|| anyhow!("no existing ancestor of {}", dir.display()))?;
+    let sh = Shell::new()?;
+    let output = cmd!(sh, "df -Pk {dir}").read()?;
+    output
+        .lines()
+        .nth(1)
+        .and_then(|l| l.split_whitespace().nth(3))
+        .and_then(|a| a.parse().ok())
+        .ok_or_else(|| anyhow!("cannot parse df output: {}", output))
+}
+
+pub fn check_disk_space(dir: &Path, required_gb: u64) -> anyhow::Result<()> {
+    let available_gb = available_kib(dir)? / (1024 * 1024);
+    info!(
+        "preflight: {} GB available in {}, estimated requirement {} GB",
+        available_gb,
+        dir.display(),
+        required_gb
+    );
+    if available_gb < required_gb {
+        return Err(anyhow!(
+            "preflight: only {} GB available in {}, but the projects are estimated to need {} GB",
+            available_gb,
+            dir.display(),
+            required_gb
+        ));
+    }
+    Ok(())
+}
+
+pub fn check_reachable(git_url: &str) -> anyhow::Result<()> {
+    let mut remote = Remote::create_detached(git_url)?;
+    remote
+        .connect(Direction::Fetch)
+        .map_err(|e| anyhow!("preflight: cannot reach {}: {}", git_url, e))?;
+    info!("preflight: {} is reachable", git_url);
+    Ok(())
+}
+
+/// Check, without cloning, that `git_url` is reachable and advertises `refspec` as a branch,
+/// tag, or other ref.  Commit hashes can't be looked up without fetching, so they pass
+pub fn check_refspec_reachable(git_url: &str, refspec: &str) -> anyhow::Result<()> {
+    let mut remote = Remote::create_detached(git_url)?;
+    let connection = remote
+        .connect_auth(Direction::Fetch, None, None)
+        .map_err(|e| anyhow!("cannot reach {}: {}", git_url, e))?;
+    let is_hash = refspec.len() >= 7 && refspec.chars().all(|c| c.is_ascii_hexdigit());
+    let found = connection.list()?.iter().any(|head| {
+        let name = head.name();
+        name == refspec
+            || name == format!("refs/heads/{}", refspec)
+            || name == format!("refs/tags/{}", refspec)
+    });
+    if !found && !is_hash {
+        return Err(anyhow!("{} has no branch or tag {}", git_url, refspec));
+    }
+    Ok(())
+}
+
+/// Why a Verus binary last modified at `binary_mtime` looks stale, if it does: it predates the
+/// HEAD commit of the Verus source it was supposedly built from, or it is older than `max_age`
+pub fn binary_staleness(
+    binary_mtime: SystemTime,
+    source_head_time: Option<SystemTime>,
+    max_age: Option<Duration>,
+    now: SystemTime,
+) -> Option<String> {
+    if let Some(head) = source_head_time {
+        if binary_mtime < head {
+            return Some(
+                "the verus binary is older than the verus source's HEAD commit; \
+                 it may need to be rebuilt"
+                    .to_string(),
+            );
+        }
+    }
+    let age = now.duration_since(binary_mtime).unwrap_or_default();
+    match max_age {
+        Some(max_age) if age > max_age => Some(format!(
+            "the verus binary was built {} hours ago, more than the allowed {}",
+            age.as_secs() / 3600,
+            max_age.as_secs() / 3600
+        )),
+        _ => None,
+    }
+}
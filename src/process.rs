@@ -0,0 +1,71 @@
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+/// How often a command with a timeout is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Like [`Command::output`], but when `timeout` is set and the command is still running after
+/// it, kill the command and everything it started (e.g., Verus's solver processes) and return
+/// what was captured so far.  `on_timeout` runs first, to stop what the command started outside
+/// its process group (e.g., a container).  The flag is whether the command was killed
+pub fn output_with_timeout(
+    mut cmd: Command,
+    timeout: Option<Duration>,
+    on_timeout: impl FnOnce(),
+) -> std::io::Result<(Output, bool)> {
+    let Some(timeout) = timeout else {
+        return cmd.output().map(|output| (output, false));
+    };
+    // A process group of its own, so the whole tree can be killed at once
+    let mut child = cmd
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let read_to_end = |mut pipe: Box<dyn Read + Send>| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            pipe.read_to_end(&mut buf).map(|_| buf)
+        })
+    };
+    let stdout = read_to_end(Box::new(child.stdout.take().expect("piped stdout")));
+    let stderr = read_to_end(Box::new(child.stderr.take().expect("piped stderr")));
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            timed_out = true;
+            on_timeout();
+            kill_process_group(child.id());
+            break child.wait()?;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    let join = |reader: std::thread::JoinHandle<std::io::Result<Vec<u8>>>| {
+        reader.join().expect("pipe reader panicked")
+    };
+    Ok((
+        Output {
+            status,
+            stdout: join(stdout)?,
+            stderr: join(stderr)?,
+        },
+        timed_out,
+    ))
+}
+
+/// SIGKILL every process in the group led by `pgid`
+fn kill_process_group(pgid: u32) {
+    let killed = Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", pgid)])
+        .status();
+    if !killed.is_ok_and(|s| s.success()) {
+        tracing::warn!("cannot kill process group {}", pgid);
+    }
+}
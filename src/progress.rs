@@ -0,0 +1,80 @@
+use std::io::{IsTerminal, Write};
+use std::time::Instant;
+
+/// Repainted status lines on stderr, one per project in flight (so more than one under
+/// `--jobs`), shown only when stdout is a terminal so that logs captured by CI stay clean
+pub struct Progress {
+    total: usize,
+    start: Instant,
+    enabled: bool,
+    /// Index and name of each running project, in the order they started
+    active: Vec<(usize, String)>,
+    /// Lines drawn by the last repaint, which the next one replaces
+    drawn: usize,
+}
+
+impl Progress {
+    pub fn new(total: usize, quiet: bool) -> Self {
+        Progress {
+            total,
+            start: Instant::now(),
+            enabled: !quiet && std::io::stdout().is_terminal(),
+            active: Vec::new(),
+            drawn: 0,
+        }
+    }
+
+    /// Show that the `index`th project (counting from 0) is starting
+    pub fn update(&mut self, index: usize, name: &str) {
+        self.active.push((index, name.to_string()));
+        self.repaint();
+    }
+
+    /// Stop showing the `index`th project
+    pub fn finished(&mut self, index: usize) {
+        self.active.retain(|(i, _)| *i != index);
+        self.repaint();
+    }
+
+    fn repaint(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        let elapsed = self.start.elapsed().as_secs();
+        let lines: Vec<String> = self
+            .active
+            .iter()
+            .map(|(index, name)| {
+                format!(
+                    "project {} of {}, {:02}:{:02}:{:02} elapsed: {}",
+                    index + 1,
+                    self.total,
+                    elapsed / 3600,
+                    elapsed / 60 % 60,
+                    elapsed % 60,
+                    name
+                )
+            })
+            .collect();
+        let mut stderr = std::io::stderr();
+        // Best effort: a failed repaint is not worth aborting the run over
+        let _ = write!(stderr, "{}{}", self.clear(), lines.join("\n"));
+        let _ = stderr.flush();
+        self.drawn = lines.len();
+    }
+
+    /// Move back to the first drawn line and clear everything after it
+    fn clear(&self) -> String {
+        let up = match self.drawn {
+            0 | 1 => String::new(),
+            n => format!("\x1b[{}A", n - 1),
+        };
+        format!("{}\r\x1b[J", up)
+    }
+
+    pub fn finish(&self) {
+        if self.enabled {
+            let _ = write!(std::io::stderr(), "{}", self.clear());
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::config::RunConfigurationProject;
+use crate::output::VerusOutput;
+use crate::{ProjectStatus, ProjectSummary};
+use anyhow::anyhow;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// One project's result, as read back from its output JSON
+pub struct ProjectReport {
+    pub name: String,
+    pub status: String,
+    pub verified: Option<u64>,
+    pub errors: Option<u64>,
+    pub duration_ms: Option<u64>,
+    pub estimated_cpu_time_ms: Option<u64>,
+}
+
+/// Each project's output JSON in a run's output directory, in name order
+fn read_output_dir(dir: &Path) -> anyhow::Result<Vec<(PathBuf, serde_json::Value)>> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| anyhow!("cannot read {}: {}", dir.display(), e))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "json"))
+        .collect();
+    paths.sort();
+    let mut outputs = Vec::new();
+    for path in paths {
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(|e| anyhow!("cannot parse {}: {}", path.display(), e))?;
+        // Skip JSON that isn't a per-project result (e.g. a --summary-json written here)
+        if json["runner"]["run_configuration"]["name"].is_string() {
+            outputs.push((path, json));
+        }
+    }
+    Ok(outputs)
+}
+
+/// The status recorded in a project's `runner` block.  Outputs from before `status` was
+/// recorded only have Verus's exit status
+fn recorded_status(runner: &serde_json::Value) -> String {
+    match (runner["status"].as_str(), runner["success"].as_bool()) {
+        (Some(status), _) => status.to_string(),
+        (None, Some(true)) => "success".to_string(),
+        (None, Some(false)) => "failure".to_string(),
+        (None, None) if runner["unchanged"] == true => "unchanged".to_string(),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// Read each project's output JSON from a run's output directory, in name order
+pub fn load_output_dir(dir: &Path) -> anyhow::Result<Vec<ProjectReport>> {
+    let mut reports = Vec::new();
+    for (_, json) in read_output_dir(dir)? {
+        let runner = &json["runner"];
+        let verus_output = serde_json::from_value::<VerusOutput>(json.clone()).ok();
+        reports.push(ProjectReport {
+            name: runner["run_configuration"]["name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            status: recorded_status(runner),
+            verified: verus_output.as_ref().map(|o| o.total_verified()),
+            errors: verus_output.as_ref().map(|o| o.total_errors()),
+            // Older outputs stored the duration as a float
+            duration_ms: runner["verification_duration_ms"]
+                .as_f64()
+                .map(|d| d as u64),
+            estimated_cpu_time_ms: verus_output
+                .as_ref()
+                .and_then(|o| o.estimated_cpu_time_ms()),
+        });
+    }
+    Ok(reports)
+}
+
+/// Like [`load_output_dir`], as [`ProjectSummary`]s for [`crate::output::html_report`].  Only
+/// what the output JSON records is filled in (e.g., not the project's commit), and an unknown
+/// status is read as an infrastructure error
+pub fn load_project_summaries(dir: &Path) -> anyhow::Result<Vec<ProjectSummary>> {
+    let mut summaries = Vec::new();
+    for (path, json) in read_output_dir(dir)? {
+        let runner = &json["runner"];
+        let project: RunConfigurationProject =
+            serde_json::from_value(runner["run_configuration"].clone())
+                .map_err(|e| anyhow!("cannot read the project in {}: {}", path.display(), e))?;
+        summaries.push(ProjectSummary {
+            project,
+            status: serde_json::from_value(recorded_status(runner).into())
+                .unwrap_or(ProjectStatus::InfrastructureError),
+            hash: String::new(),
+            verification_duration: Duration::from_millis(
+                runner["verification_duration_ms"].as_f64().unwrap_or(0.0) as u64,
+            ),
+            verus_output: serde_json::from_value(json.clone()).ok(),
+            result_fingerprint: runner["result_fingerprint"].as_str().map(String::from),
+            seed_stable: runner["seed_stable"].as_bool(),
+            clone_size_bytes: runner["clone_size_bytes"].as_u64(),
+            revision_change: runner["revision_change"].as_str().map(String::from),
+            smt_hash: runner["smt_hash"].as_str().map(String::from),
+        });
+    }
+    Ok(summaries)
+}
+
+#[derive(Clone, Copy)]
+pub enum DurationUnit {
+    Millis,
+    /// Seconds with the given number of decimal places
+    Seconds(usize),
+}
+
+impl DurationUnit {
+    fn suffix(self) -> &'static str {
+        match self {
+            DurationUnit::Millis => "ms",
+            DurationUnit::Seconds(_) => "s",
+        }
+    }
+
+    fn format(self, ms: u64) -> String {
+        match self {
+            DurationUnit::Millis => ms.to_string(),
+            DurationUnit::Seconds(decimals) => format!("{:.*}", decimals, ms as f64 / 1000.0),
+        }
+    }
+}
+
+/// A Markdown table with one row per project
+pub fn markdown(reports: &[ProjectReport], unit: DurationUnit) -> String {
+    let cell = |v: Option<u64>| v.map_or("-".to_string(), |v| v.to_string());
+    let ratio = |r: &ProjectReport| {
+        crate::output::pass_ratio(r.verified?, r.errors?).map(|p| format!("{:.1}%", p * 100.0))
+    };
+    let mut out = format!(
+        "| project | status | verified | errors | pass ratio | duration ({unit}) | CPU time ({unit}) |\n\
+         |---|---|---|---|---|---|---|\n",
+        unit = unit.suffix()
+    );
+    for r in reports {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            r.name,
+            r.status,
+            cell(r.verified),
+            cell(r.errors),
+            ratio(r).unwrap_or_else(|| "-".to_string()),
+            r.duration_ms.map_or("-".to_string(), |d| unit.format(d)),
+            r.estimated_cpu_time_ms
+                .map_or("-".to_string(), |d| unit.format(d))
+        ));
+    }
+    out
+}
@@ -0,0 +1,51 @@
+use anyhow::anyhow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File name, under `--cache-dir` or `--output-dir`, of the commit each project was last
+/// verified at
+pub const STATE_FILE_NAME: &str = "revisions.json";
+
+/// The commit each project was at in the last run that verified it successfully, used by
+/// `--skip-unchanged-revs` to skip reruns of a branch that has not moved.  Keyed by the
+/// project's name (after `solver_matrix` expansion) and the Verus build and configuration it
+/// was verified with, so a state shared between runs never skips a project those have not
+/// verified
+pub struct RevisionState {
+    path: PathBuf,
+    /// As loaded when the run started.  Projects are only compared against this, so that
+    /// one project's result can't make another in the same run look unchanged
+    previous: BTreeMap<String, String>,
+    /// `previous`, updated with this run's results
+    current: BTreeMap<String, String>,
+}
+
+impl RevisionState {
+    /// An empty state if `path` does not exist yet
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let previous: BTreeMap<String, String> = match std::fs::read_to_string(path) {
+            Ok(s) => serde_json::from_str(&s)
+                .map_err(|e| anyhow!("cannot parse {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(anyhow!("cannot read {}: {}", path.display(), e)),
+        };
+        Ok(RevisionState {
+            path: path.to_path_buf(),
+            current: previous.clone(),
+            previous,
+        })
+    }
+
+    /// The commit the project under `key` was verified at when the run started
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.previous.get(key).map(|h| h.as_str())
+    }
+
+    /// Record that the project under `key` verified at `hash`, and save immediately, so an
+    /// interrupted run keeps what it verified
+    pub fn record(&mut self, key: &str, hash: &str) -> anyhow::Result<()> {
+        self.current.insert(key.to_string(), hash.to_string());
+        std::fs::write(&self.path, serde_json::to_string_pretty(&self.current)?)
+            .map_err(|e| anyhow!("cannot write {}: {}", self.path.display(), e))
+    }
+}
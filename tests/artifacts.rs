@@ -0,0 +1,18 @@
+use std::fs;
+use tempdir::TempDir;
+use verita::artifacts::smt_digest;
+
+#[test]
+fn smt_digest_ignores_comments() {
+    let root = TempDir::new("verita-test").unwrap();
+    let dir = root.path().join("smt");
+    assert_eq!(smt_digest(&dir).unwrap(), None);
+    fs::create_dir_all(dir.join("module")).unwrap();
+    let query = dir.join("module/root.smt2");
+    fs::write(&query, "; generated 10:00\n(declare-fun f () Int)\n").unwrap();
+    let digest = smt_digest(&dir).unwrap().unwrap();
+    fs::write(&query, "; generated 10:05\n\n(declare-fun f () Int)\n").unwrap();
+    assert_eq!(smt_digest(&dir).unwrap().as_ref(), Some(&digest));
+    fs::write(&query, "(declare-fun g () Int)\n").unwrap();
+    assert_ne!(smt_digest(&dir).unwrap(), Some(digest));
+}
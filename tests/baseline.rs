@@ -0,0 +1,84 @@
+use std::fs;
+use tempdir::TempDir;
+use verita::baseline::Baseline;
+use verita::ProjectStatus;
+
+fn load(json: serde_json::Value) -> Baseline {
+    let root = TempDir::new("verita-test").unwrap();
+    let path = root.path().join("baseline.json");
+    fs::write(&path, json.to_string()).unwrap();
+    Baseline::load(&path).unwrap()
+}
+
+#[test]
+fn smt_function_count_changes_are_reported() {
+    let baseline = load(serde_json::json!({"projects": [
+        {"name": "counted", "hash": "", "smt_function_count": 3},
+        {"name": "uncounted", "hash": "", "smt_function_count": null},
+    ]}));
+    assert_eq!(baseline.smt_function_count_change("counted", 3), None);
+    assert_eq!(
+        baseline.smt_function_count_change("counted", 5).as_deref(),
+        Some("3→5")
+    );
+    assert_eq!(baseline.smt_function_count_change("uncounted", 5), None);
+    assert_eq!(baseline.smt_function_count_change("missing", 5), None);
+}
+
+#[test]
+fn only_failures_after_a_pass_are_new_regressions() {
+    let baseline = load(serde_json::json!({"projects": [
+        {"name": "passed", "hash": "", "status": "success"},
+        {"name": "failed", "hash": "", "status": "failure"},
+        {"name": "old", "hash": ""},
+    ]}));
+    assert!(baseline.newly_regressed("passed", ProjectStatus::Failure));
+    assert!(baseline.newly_regressed("passed", ProjectStatus::TimedOut));
+    assert!(!baseline.newly_regressed("passed", ProjectStatus::Success));
+    assert!(!baseline.newly_regressed("passed", ProjectStatus::AllowedFailure));
+    assert!(!baseline.newly_regressed("passed", ProjectStatus::InfrastructureError));
+    assert!(!baseline.newly_regressed("failed", ProjectStatus::Failure));
+    assert!(!baseline.newly_regressed("old", ProjectStatus::Failure));
+    assert!(!baseline.newly_regressed("missing", ProjectStatus::Failure));
+}
+
+#[test]
+fn pass_ratio_changes_are_reported_as_percentages() {
+    let baseline = load(serde_json::json!({
+        "pass_ratio": 0.9,
+        "projects": [{"name": "fixture", "hash": "", "pass_ratio": 0.95}],
+    }));
+    assert_eq!(
+        baseline.pass_ratio_change("fixture", 0.8).as_deref(),
+        Some("95.0%→80.0%")
+    );
+    assert_eq!(baseline.pass_ratio_change("fixture", 0.950001), None);
+    assert_eq!(baseline.pass_ratio_change("missing", 0.8), None);
+    assert_eq!(
+        baseline.suite_pass_ratio_change(1.0).as_deref(),
+        Some("90.0%→100.0%")
+    );
+}
+
+#[test]
+fn estimated_cpu_time_changes_are_reported_in_seconds() {
+    let baseline = load(serde_json::json!({
+        "estimated_cpu_time_ms": 0,
+        "projects": [
+            {"name": "fixture", "hash": "", "estimated_cpu_time_ms": 2000},
+            {"name": "old", "hash": ""},
+        ],
+    }));
+    assert_eq!(
+        baseline
+            .estimated_cpu_time_change("fixture", 3000)
+            .as_deref(),
+        Some("2.0s→3.0s (+50%)")
+    );
+    assert_eq!(baseline.estimated_cpu_time_change("fixture", 2010), None);
+    assert_eq!(baseline.estimated_cpu_time_change("old", 3000), None);
+    assert_eq!(
+        baseline.total_estimated_cpu_time_change(1500).as_deref(),
+        Some("0.0s→1.5s")
+    );
+}
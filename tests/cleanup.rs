@@ -0,0 +1,19 @@
+use std::fs;
+use tempdir::TempDir;
+use verita::cleanup::remove_dir_all_resilient;
+
+#[test]
+fn removes_read_only_trees() {
+    let root = TempDir::new("verita-test").unwrap();
+    let dir = root.path().join("clone");
+    fs::create_dir_all(dir.join(".git/objects/pack")).unwrap();
+    let pack = dir.join(".git/objects/pack/pack-0.pack");
+    fs::write(&pack, "pack").unwrap();
+    for path in [&pack, &dir.join(".git/objects/pack")] {
+        let mut permissions = fs::metadata(path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(path, permissions).unwrap();
+    }
+    remove_dir_all_resilient(&dir).unwrap();
+    assert!(!dir.exists());
+}
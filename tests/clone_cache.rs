@@ -0,0 +1,12 @@
+use verita::clone_cache::cache_key;
+
+#[test]
+fn cache_keys_are_readable_but_distinct() {
+    let key = cache_key("https://github.com/verus-lang/verus.git");
+    assert!(key.starts_with("https___github.com_verus-lang_verus.git-"));
+    assert_eq!(key, cache_key("https://github.com/verus-lang/verus.git"));
+    assert_ne!(
+        cache_key("https://host/a/b_c"),
+        cache_key("https://host/a/b/c")
+    );
+}
@@ -0,0 +1,13 @@
+use verita::shell_command_line;
+
+#[test]
+fn command_lines_are_shell_quoted() {
+    let mut cmd = std::process::Command::new("/bin/bash");
+    cmd.args(["-c", "echo it's done"])
+        .env("RUSTUP_TOOLCHAIN", "1.76.0")
+        .current_dir("/tmp/my project");
+    assert_eq!(
+        shell_command_line(&cmd),
+        r"cd '/tmp/my project' && RUSTUP_TOOLCHAIN=1.76.0 /bin/bash -c 'echo it'\''s done'"
+    );
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+use std::process::Command;
+use verita::container::{Container, Mount};
+
+#[test]
+fn commands_keep_their_directory_and_environment() {
+    let container = Container {
+        runtime: "podman".to_string(),
+        image: "verus-ci:latest".to_string(),
+        mounts: vec![
+            Mount {
+                path: PathBuf::from("/opt/verus"),
+                writable: false,
+            },
+            Mount {
+                path: PathBuf::from("/tmp/verita"),
+                writable: true,
+            },
+        ],
+        user: Some("1000:100".to_string()),
+    };
+    let mut cmd = Command::new("/bin/bash");
+    cmd.args(["-c", "./prepare.sh"])
+        .current_dir("/tmp/verita/fixture")
+        .env("RUSTUP_TOOLCHAIN", "1.80");
+    let wrapped = container.wrap(&cmd, "verita-fixture-1");
+    assert_eq!(wrapped.get_program(), "podman");
+    let args: Vec<_> = wrapped.get_args().map(|a| a.to_str().unwrap()).collect();
+    assert_eq!(
+        args,
+        [
+            "run",
+            "--rm",
+            "--name",
+            "verita-fixture-1",
+            "--user",
+            "1000:100",
+            "-v",
+            "/opt/verus:/opt/verus:ro",
+            "-v",
+            "/tmp/verita:/tmp/verita",
+            "-w",
+            "/tmp/verita/fixture",
+            "-e",
+            "RUSTUP_TOOLCHAIN=1.80",
+            "verus-ci:latest",
+            "/bin/bash",
+            "-c",
+            "./prepare.sh",
+        ]
+    );
+}
+
+#[test]
+fn names_keep_apart_projects_that_differ_only_in_replaced_characters() {
+    let name = Container::name("a@z3");
+    assert!(name.starts_with("verita-a_z3-"), "{}", name);
+    assert!(name.ends_with(&format!("-{}", std::process::id())));
+    assert_ne!(name, Container::name("a_z3"));
+    assert_eq!(name, Container::name("a@z3"));
+}
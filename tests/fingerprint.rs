@@ -0,0 +1,97 @@
+use verita::fingerprint::Sha256;
+
+fn sha256(chunks: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    hasher.finish_hex()
+}
+
+#[test]
+fn sha256_matches_the_standard_test_vectors() {
+    assert_eq!(
+        sha256(&[]),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        sha256(&[b"abc"]),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    // Padding spills into a second block, and chunks straddle block boundaries
+    assert_eq!(
+        sha256(&[
+            b"abcdbcdecdefdefgefghfghighij",
+            b"hijkijkljklmklmnlmnomnopnopq"
+        ]),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+    assert_eq!(
+        sha256(&[
+            b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmn",
+            b"hijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+        ]),
+        "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1"
+    );
+    let a = vec![b'a'; 1000];
+    assert_eq!(
+        sha256(&vec![a.as_slice(); 1000]),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
+
+#[test]
+fn sha256_pads_messages_around_block_boundaries() {
+    // 55 bytes is the longest message whose padding fits in one block, and 119 in two
+    for (len, digest) in [
+        (
+            55,
+            "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318",
+        ),
+        (
+            56,
+            "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a",
+        ),
+        (
+            63,
+            "7d3e74a05d7db15bce4ad9ec0658ea98e3f06eeecf16b4c6fff2da457ddc2f34",
+        ),
+        (
+            64,
+            "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb",
+        ),
+        (
+            65,
+            "635361c48bb9eab14198e76ea8ab7f1a41685d6ad62aa9146d301d4f17eb0ae0",
+        ),
+        (
+            119,
+            "31eba51c313a5c08226adf18d4a359cfdfd8d2e816b13f4af952f7ea6584dcfb",
+        ),
+        (
+            120,
+            "2f3d335432c70b580af0e8e1b3674a7c020d683aa5f73aaaedfdc55af904c21c",
+        ),
+        (
+            128,
+            "6836cf13bac400e9105071cd6af47084dfacad4e5e302c94bfed24e013afb73e",
+        ),
+    ] {
+        let message = vec![b'a'; len];
+        assert_eq!(sha256(&[&message]), digest, "{} bytes", len);
+        // Byte at a time, so every length is also reached incrementally
+        let bytes: Vec<&[u8]> = message.chunks(1).collect();
+        assert_eq!(sha256(&bytes), digest, "{} bytes, byte at a time", len);
+    }
+}
+
+#[test]
+fn file_digest_hashes_file_contents() {
+    let dir = tempdir::TempDir::new("verita-test").unwrap();
+    let path = dir.path().join("binary");
+    std::fs::write(&path, vec![b'a'; 1_000_000]).unwrap();
+    assert_eq!(
+        verita::fingerprint::file_digest(&path).unwrap(),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
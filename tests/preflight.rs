@@ -0,0 +1,37 @@
+use git2::{Repository, Signature};
+use std::time::{Duration, SystemTime};
+use tempdir::TempDir;
+use verita::preflight::{binary_staleness, check_refspec_reachable};
+
+#[test]
+fn refspecs_are_checked_without_cloning() {
+    let root = TempDir::new("verita-test").unwrap();
+    let repo = Repository::init(root.path()).unwrap();
+    let sig = Signature::now("test", "test@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    repo.commit(Some("refs/heads/main"), &sig, &sig, "init", &tree, &[])
+        .unwrap();
+    let url = root.path().to_str().unwrap();
+
+    check_refspec_reachable(url, "main").unwrap();
+    let err = check_refspec_reachable(url, "renamed").unwrap_err();
+    assert!(err.to_string().contains("no branch or tag renamed"));
+    assert!(check_refspec_reachable(&format!("{}/missing", url), "main").is_err());
+}
+
+#[test]
+fn stale_binaries_are_detected() {
+    let now = SystemTime::now();
+    let hour = Duration::from_secs(3600);
+    let built = now - 2 * hour;
+    assert_eq!(binary_staleness(built, Some(built - hour), None, now), None);
+    assert!(binary_staleness(built, Some(built + hour), None, now)
+        .unwrap()
+        .contains("older than the verus source"));
+    assert_eq!(binary_staleness(built, None, Some(3 * hour), now), None);
+    assert!(binary_staleness(built, None, Some(hour), now)
+        .unwrap()
+        .contains("built 2 hours ago"));
+}
@@ -0,0 +1,1896 @@
+use clap::Parser as ClapParser;
+use git2::{Repository, Signature};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use tempdir::TempDir;
+use verita::report::DurationUnit;
+use verita::{Args, ProjectStatus};
+
+/// A fake Verus checkout whose `verus` binary prints `canned_output` and exits successfully
+/// (or with `STUB_VERUS_EXIT`)
+fn make_verus_repo(root: &Path, canned_output: &str) -> PathBuf {
+    let verus_repo = root.join("verus");
+    let source = verus_repo.join("source");
+    for krate in ["builtin", "vstd"] {
+        fs::create_dir_all(source.join(krate)).unwrap();
+        fs::write(
+            source.join(krate).join("Cargo.toml"),
+            format!("[package]\nname = \"{krate}\"\nversion = \"0.1.0\"\n"),
+        )
+        .unwrap();
+    }
+    let release = source.join("target-verus/release");
+    fs::create_dir_all(&release).unwrap();
+    let canned_path = release.join("canned-output.json");
+    fs::write(&canned_path, canned_output).unwrap();
+    let verus = release.join("verus");
+    fs::write(
+        &verus,
+        // The exit status (and a hang, in a child process) can be set through the prepare
+        // script's VERITA_PREPARE_ENV
+        format!(
+            "#!/bin/sh\ncat '{}'\n${{STUB_VERUS_SLEEP:+sleep $STUB_VERUS_SLEEP}}\nexit ${{STUB_VERUS_EXIT:-0}}\n",
+            canned_path.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&verus, fs::Permissions::from_mode(0o755)).unwrap();
+    verus_repo
+}
+
+/// A git repository with a single commit on `main` containing a trivial crate
+fn make_project_repo(root: &Path) -> (PathBuf, String) {
+    let project = root.join("project-origin");
+    let repo = Repository::init(&project).unwrap();
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"fixture\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(project.join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("verita", "verita@example.com").unwrap();
+    let commit = repo
+        .commit(Some("refs/heads/main"), &sig, &sig, "initial", &tree, &[])
+        .unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+    (project, commit.to_string())
+}
+
+fn write_config(root: &Path, project_repo: &Path, extra_project_config: &str) -> PathBuf {
+    let config = root.join("config.toml");
+    fs::write(
+        &config,
+        format!(
+            r#"
+verus_git_url = "https://github.com/verus-lang/verus.git"
+verus_refspec = "main"
+verus_features = ["singular"]
+
+[[project]]
+name = "fixture"
+git_url = "{}"
+refspec = "main"
+crate_root = "src/lib.rs"
+{}
+"#,
+            project_repo.display(),
+            extra_project_config
+        ),
+    )
+    .unwrap();
+    config
+}
+
+fn run_with_canned_output(canned_output: &str) -> (TempDir, verita::RunSummary, String) {
+    run_with_project_config(canned_output, "")
+}
+
+fn run_with_project_config(
+    canned_output: &str,
+    extra_project_config: &str,
+) -> (TempDir, verita::RunSummary, String) {
+    run_with_args(canned_output, extra_project_config, &[])
+}
+
+fn run_with_args(
+    canned_output: &str,
+    extra_project_config: &str,
+    extra_args: &[&str],
+) -> (TempDir, verita::RunSummary, String) {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(root.path(), canned_output);
+    let (project_repo, hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, extra_project_config);
+    let summary = run_in(root.path(), &verus_repo, &config, extra_args);
+    (root, summary, hash)
+}
+
+/// Run Verita on an existing test setup, with output under `root`
+fn run_in(
+    root: &Path,
+    verus_repo: &Path,
+    config: &Path,
+    extra_args: &[&str],
+) -> verita::RunSummary {
+    let output_dir = root.join("output");
+    let mut argv: Vec<&OsStr> = vec![
+        "verita".as_ref(),
+        "--verus-repo".as_ref(),
+        verus_repo.as_os_str(),
+        "--output-dir".as_ref(),
+        output_dir.as_os_str(),
+        "--label".as_ref(),
+        "test".as_ref(),
+        "--no-preflight".as_ref(),
+    ];
+    // Extra arguments may refer to the test's root directory as `{root}`
+    let extra_args: Vec<String> = extra_args
+        .iter()
+        .map(|a| a.replace("{root}", &root.display().to_string()))
+        .collect();
+    argv.extend(extra_args.iter().map(OsStr::new));
+    argv.push(config.as_os_str());
+    let args = Args::try_parse_from(argv).unwrap();
+    verita::run(&args).unwrap()
+}
+
+fn read_output_json(summary: &verita::RunSummary, name: &str) -> serde_json::Value {
+    let path = summary.output_path.join(name).with_extension("json");
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn single_crate_output() {
+    let (_root, summary, hash) =
+        run_with_canned_output(include_str!("fixtures/verus-output-single.json"));
+    assert_eq!(summary.project_summaries.len(), 1);
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.project.name, "fixture");
+    assert_eq!(project.status, ProjectStatus::Success);
+    assert_eq!(project.hash, hash);
+    assert!(project.clone_size_bytes.is_some_and(|size| size > 0));
+    let verus_output = project.verus_output.as_ref().expect("parsed verus output");
+    let results = verus_output.crate_results();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].crate_name(), None);
+    assert_eq!(results[0].verified(), Some(12));
+
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["success"], true);
+    assert_eq!(output_json["runner"]["crate_mode"], "cargo");
+    assert!(output_json["runner"]["verus_binary_mtime"].is_string());
+    assert_eq!(output_json["runner"]["label"], "test");
+    assert_eq!(
+        output_json["runner"]["run_configuration"]["name"],
+        "fixture"
+    );
+    assert_eq!(output_json["verification-results"]["verified"], 12);
+}
+
+#[test]
+fn per_crate_output() {
+    let (_root, summary, _hash) =
+        run_with_canned_output(include_str!("fixtures/verus-output-per-crate.json"));
+    let verus_output = summary.project_summaries[0]
+        .verus_output
+        .as_ref()
+        .expect("parsed verus output");
+    let results: Vec<_> = verus_output
+        .crate_results()
+        .iter()
+        .map(|r| (r.crate_name(), r.verified(), r.errors()))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            (Some("alpha"), Some(5), Some(0)),
+            (Some("beta"), Some(7), Some(2)),
+        ]
+    );
+    assert_eq!(verus_output.pass_ratio(), Some(12.0 / 14.0));
+    assert_eq!(verus_output.functions_per_second(), Some(4.0));
+}
+
+#[test]
+fn invalid_output_json() {
+    let (_root, summary, _hash) = run_with_canned_output("not json");
+    assert!(summary.project_summaries[0].verus_output.is_none());
+    assert_eq!(summary.unparseable_output_count(), 1);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["invalid_output_json"], true);
+}
+
+#[test]
+fn success_hook_sees_result() {
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-per-crate.json"),
+        r#"
+on_success_script = "echo $VERITA_STATUS $VERITA_ERRORS $VERITA_OUTPUT_JSON > $VERITA_OUTPUT_JSON.hook"
+on_failure_script = "echo failure > $VERITA_OUTPUT_JSON.hook"
+"#,
+    );
+    let output_json = fs::canonicalize(summary.output_path.join("fixture.json")).unwrap();
+    let hook = fs::read_to_string(output_json.with_extension("json.hook")).unwrap();
+    assert_eq!(hook.trim(), format!("success 2 {}", output_json.display()));
+}
+
+#[test]
+fn archive_only_replaces_output_dir() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--archive-only"],
+    );
+    assert!(!summary.output_path.exists());
+    let archive_path = summary.archive_path.expect("archive path");
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(
+        fs::File::open(archive_path).unwrap(),
+    ));
+    let dir_name = summary.output_path.file_name().unwrap();
+    let entries: Vec<PathBuf> = archive
+        .entries()
+        .unwrap()
+        .map(|e| e.unwrap().path().unwrap().into_owned())
+        .collect();
+    assert!(entries.contains(&Path::new(dir_name).join("fixture.json")));
+}
+
+#[test]
+fn timestamp_format_gets_milliseconds_unless_it_has_them() {
+    let dir_name = |format: &str| {
+        let (_root, summary, _hash) = run_with_args(
+            include_str!("fixtures/verus-output-single.json"),
+            "",
+            &["--timestamp-format", format],
+        );
+        let name = summary.output_path.file_name().unwrap();
+        name.to_str().unwrap().to_string()
+    };
+    assert!(regex::Regex::new(r"^\d{4}-\d{3}-test$")
+        .unwrap()
+        .is_match(&dir_name("%Y")));
+    assert!(regex::Regex::new(r"^\d{4}\.\d{3}-test$")
+        .unwrap()
+        .is_match(&dir_name("%Y%.3f")));
+}
+
+#[test]
+fn since_skips_old_commits() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--since", "2999-01-01"],
+    );
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.status, ProjectStatus::Unchanged);
+    assert!(project.verus_output.is_none());
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["unchanged"], true);
+
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--since", "2999-01-01", "--force"],
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--since", "2000-01-01T00:00:00Z"],
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+}
+
+#[test]
+fn stream_writes_one_line_per_project() {
+    let (root, _summary, hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--stream", "{root}/stream.jsonl"],
+    );
+    let stream = fs::read_to_string(root.path().join("stream.jsonl")).unwrap();
+    let lines: Vec<serde_json::Value> = stream
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["name"], "fixture");
+    assert_eq!(lines[0]["status"], "success");
+    assert_eq!(lines[0]["hash"], hash);
+}
+
+#[test]
+fn explicit_verus_binary() {
+    let other = TempDir::new("verita-test").unwrap();
+    let other_verus = make_verus_repo(
+        other.path(),
+        include_str!("fixtures/verus-output-per-crate.json"),
+    )
+    .join("source/target-verus/release/verus");
+    let other_verus = other_verus.display().to_string();
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--verus-binary", &other_verus],
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["verus_binary"], other_verus);
+    assert_eq!(
+        summary.project_summaries[0]
+            .verus_output
+            .as_ref()
+            .unwrap()
+            .crate_results()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn events_bracket_each_project() {
+    let (root, _summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--events", "{root}/events.jsonl"],
+    );
+    let events = fs::read_to_string(root.path().join("events.jsonl")).unwrap();
+    let events: Vec<serde_json::Value> = events
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let phases: Vec<_> = events
+        .iter()
+        .map(|e| e["phase"].as_str().unwrap())
+        .collect();
+    assert_eq!(phases, ["project_started", "project_finished"]);
+    assert!(events.iter().all(|e| e["project"] == "fixture"));
+    assert_eq!(events[1]["status"], "success");
+}
+
+#[test]
+fn artifacts_are_collected() {
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-single.json"),
+        r#"collect_artifacts = ["*.rs", "*.smt2"]"#,
+    );
+    let artifacts = summary.output_path.join("fixture/artifacts");
+    assert_eq!(
+        fs::read_to_string(artifacts.join("lib.rs")).unwrap(),
+        "pub fn f() {}\n"
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(
+        output_json["runner"]["artifacts"],
+        serde_json::json!(["lib.rs"])
+    );
+}
+
+#[test]
+fn exhausted_time_budget_skips_projects() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--max-runtime", "0"],
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::NotRun);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["not_run"], "time budget exceeded");
+}
+
+#[test]
+fn warnings_as_errors_fails_projects_with_warnings() {
+    let mut output: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/verus-output-single.json")).unwrap();
+    output["verification-results"]["warnings"] = serde_json::json!(3);
+    let (_root, summary, _hash) = run_with_args(&output.to_string(), "", &[]);
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.status, ProjectStatus::Success);
+    assert_eq!(project.verus_output.as_ref().unwrap().total_warnings(), 3);
+
+    let (_root, summary, _hash) = run_with_args(&output.to_string(), "", &["--warnings-as-errors"]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Failure);
+}
+
+#[test]
+fn seeds_are_recorded() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--seeds", "3"],
+    );
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.seed_stable, Some(true));
+    let json = read_output_json(&summary, &project.project.name);
+    let seeds = json["runner"]["seeds"].as_array().unwrap();
+    assert_eq!(seeds.len(), 3);
+    assert_eq!(seeds[2]["seed"], 2);
+    assert_eq!(json["runner"]["seed_stable"], true);
+}
+
+#[test]
+fn scoped_output_is_a_partial_success() {
+    let mut output: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/verus-output-single.json")).unwrap();
+    output["verification-results"]["is-verifying-entire-crate"] = serde_json::json!(false);
+    let (_root, summary, _hash) = run_with_canned_output(&output.to_string());
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.status, ProjectStatus::PartialSuccess);
+    let json = read_output_json(&summary, &project.project.name);
+    assert_eq!(json["runner"]["scoped_verification"], true);
+}
+
+#[test]
+fn explanations() {
+    let (_root, summary, _hash) =
+        run_with_canned_output(include_str!("fixtures/verus-output-per-crate.json"));
+    assert_eq!(
+        summary.project_summaries[0].explanation(),
+        "2 of 14 functions failed to verify"
+    );
+
+    let (_root, summary, _hash) = run_with_canned_output("not json");
+    assert_eq!(
+        summary.project_summaries[0].explanation(),
+        "Verus produced no parseable output (see stderr)"
+    );
+}
+
+#[test]
+fn skip_unchanged_revs_skips_reruns_of_the_same_commit() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let args = ["--skip-unchanged-revs"];
+
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.status, ProjectStatus::Unchanged);
+    assert_eq!(project.hash, hash);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["unchanged_revision"], true);
+
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &["--skip-unchanged-revs", "--force"],
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+
+    // With a clone cache, the last-seen commits are kept in it
+    let args = ["--skip-unchanged-revs", "--cache-dir", "{root}/cache"];
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    assert!(root.path().join("cache/revisions.json").exists());
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(
+        summary.project_summaries[0].status,
+        ProjectStatus::Unchanged
+    );
+}
+
+#[test]
+fn result_fingerprint_ignores_presentation_flags_and_binary_mtime() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let fingerprint = |args: &[&str]| {
+        run_in(root.path(), &verus_repo, &config, args).project_summaries[0]
+            .result_fingerprint
+            .clone()
+            .unwrap()
+    };
+    let binary = verus_repo.join("source/target-verus/release/verus");
+
+    let first = fingerprint(&[]);
+    assert_eq!(fingerprint(&["--query-counts"]), first);
+    fs::write(&binary, fs::read(&binary).unwrap()).unwrap();
+    assert_eq!(fingerprint(&[]), first);
+    let mut contents = fs::read(&binary).unwrap();
+    contents.extend_from_slice(b"\n# rebuilt\n");
+    fs::write(&binary, contents).unwrap();
+    assert_ne!(fingerprint(&[]), first);
+}
+
+#[test]
+fn skip_unchanged_revs_reruns_projects_that_failed() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let fail = root.path().join("fail");
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "prepare_script = \"if [ -e {} ]; then echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV; fi\"",
+            fail.display()
+        ),
+    );
+    let args = ["--skip-unchanged-revs"];
+    let status = || run_in(root.path(), &verus_repo, &config, &args).project_summaries[0].status;
+
+    fs::write(&fail, "").unwrap();
+    assert_eq!(status(), ProjectStatus::Failure);
+    fs::remove_file(&fail).unwrap();
+    assert_eq!(status(), ProjectStatus::Success);
+    assert_eq!(status(), ProjectStatus::Unchanged);
+}
+
+#[test]
+fn skip_unchanged_revs_reruns_projects_after_a_verus_rebuild() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let args = ["--skip-unchanged-revs"];
+    let status = || run_in(root.path(), &verus_repo, &config, &args).project_summaries[0].status;
+
+    assert_eq!(status(), ProjectStatus::Success);
+    assert_eq!(status(), ProjectStatus::Unchanged);
+    let binary = verus_repo.join("source/target-verus/release/verus");
+    let mut contents = fs::read(&binary).unwrap();
+    contents.extend_from_slice(b"\n# rebuilt\n");
+    fs::write(&binary, contents).unwrap();
+    assert_eq!(status(), ProjectStatus::Success);
+    assert_eq!(status(), ProjectStatus::Unchanged);
+}
+
+#[test]
+fn skip_unchanged_revs_tracks_projects_sharing_a_repository_separately() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "\n[[project]]\nname = \"second\"\ngit_url = \"{}\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\n",
+            project_repo.display()
+        ),
+    );
+    let args = ["--skip-unchanged-revs"];
+    let statuses = || {
+        run_in(root.path(), &verus_repo, &config, &args)
+            .project_summaries
+            .iter()
+            .map(|p| p.status)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(statuses(), [ProjectStatus::Success, ProjectStatus::Success]);
+    assert_eq!(
+        statuses(),
+        [ProjectStatus::Unchanged, ProjectStatus::Unchanged]
+    );
+}
+
+#[test]
+fn summary_json_without_per_project_files() {
+    let (root, summary, hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &[
+            "--summary-json",
+            "{root}/summary.json",
+            "--no-per-project-files",
+        ],
+    );
+    assert!(!summary.output_path.join("fixture.json").exists());
+    let summary_json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.path().join("summary.json")).unwrap())
+            .unwrap();
+    assert_eq!(summary_json["label"], "test");
+    assert_eq!(summary_json["complete"], true);
+    assert_eq!(summary_json["host"]["os"], std::env::consts::OS);
+    assert!(summary_json["host"]["logical_cores"].as_u64().unwrap() > 0);
+    assert_eq!(summary_json["counts"]["success"], 1);
+    assert_eq!(summary_json["projects"][0]["name"], "fixture");
+    assert_eq!(summary_json["projects"][0]["hash"], hash.as_str());
+}
+
+#[test]
+fn summary_json_is_written_to_the_output_directory() {
+    let (_root, summary, hash) =
+        run_with_project_config(include_str!("fixtures/verus-output-single.json"), "");
+    let summary_json: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(summary.output_path.join("summary.json")).unwrap(),
+    )
+    .unwrap();
+    let entry = &summary_json["projects"][0];
+    assert_eq!(entry["name"], "fixture");
+    assert_eq!(entry["hash"], hash.as_str());
+    assert_eq!(entry["success"], true);
+    assert_eq!(entry["verified"], 12);
+    assert_eq!(entry["errors"], 0);
+    assert!(entry["verification_duration_ms"].is_u64());
+    // The embedded output reads back like Verus's own
+    let verus_output: verita::output::VerusOutput =
+        serde_json::from_value(entry["verus_output"].clone()).unwrap();
+    assert_eq!(verus_output.total_verified(), 12);
+    assert_eq!(verus_output.estimated_cpu_time_ms(), Some(1200));
+    // and is not mistaken for a project's output
+    let reports = verita::report::load_output_dir(&summary.output_path).unwrap();
+    assert_eq!(reports.len(), 1);
+}
+
+#[test]
+fn sqlite_sql_records_projects_and_metrics() {
+    let (_root, summary, hash) =
+        run_with_project_config(include_str!("fixtures/verus-output-single.json"), "");
+    let sql = verita::output::SqliteSink::run_sql(
+        "2024-05-01",
+        "it's nightly",
+        None,
+        &summary.project_summaries,
+    );
+    assert!(sql.starts_with("CREATE TABLE IF NOT EXISTS runs"));
+    assert!(
+        sql.contains("INSERT OR REPLACE INTO runs VALUES ('2024-05-01', 'it''s nightly', NULL);")
+    );
+    assert!(sql.contains(&format!(
+        "INSERT OR REPLACE INTO projects VALUES ('2024-05-01', 'fixture', '{hash}', 'success', "
+    )));
+    assert!(sql.contains(&format!(
+        "INSERT OR REPLACE INTO metrics VALUES ('2024-05-01', 'fixture', '{hash}', 10, 900, 910, 1500, 1200);"
+    )));
+    assert!(sql.ends_with("COMMIT;\n"));
+}
+
+#[test]
+fn sqlite_records_quoted_names_and_projects_without_a_commit() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, hash) = make_project_repo(root.path());
+    // Neither unresolved project has a commit to tell it apart from the other
+    let mut unresolved = String::new();
+    for name in [r#"it's \"odd\"; --"#, "broken"] {
+        unresolved.push_str(&format!(
+            "\n[[project]]\nname = \"{}\"\ngit_url = \"{}\"\nrefspec = \"no-such-branch\"\ncrate_root = \"src/lib.rs\"\n",
+            name,
+            project_repo.display()
+        ));
+    }
+    let config = write_config(root.path(), &project_repo, &unresolved);
+    let mut path = std::env::var_os("PATH").unwrap_or_default();
+    let has_sqlite3 = std::process::Command::new("sqlite3")
+        .arg("-version")
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if !has_sqlite3 {
+        // Python's sqlite3 module, behind the command line verita runs
+        let bin = root.path().join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        let sqlite3 = bin.join("sqlite3");
+        fs::write(
+            &sqlite3,
+            "#!/usr/bin/env python3\nimport sqlite3, sys\nargs = [a for a in sys.argv[1:] if a != '-bail']\nif args == ['-version']:\n    print('3.0.0')\n    sys.exit()\ndb = sqlite3.connect(args[0], isolation_level=None)\nif len(args) > 1:\n    for row in db.execute(args[1]):\n        print('|'.join(map(str, row)))\nelse:\n    db.executescript(sys.stdin.read())\n",
+        )
+        .unwrap();
+        fs::set_permissions(&sqlite3, fs::Permissions::from_mode(0o755)).unwrap();
+        if !std::process::Command::new(&sqlite3)
+            .arg("-version")
+            .output()
+            .is_ok_and(|o| o.status.success())
+        {
+            eprintln!("skipping: neither sqlite3 nor python3 is available");
+            return;
+        }
+        path =
+            std::env::join_paths(std::iter::once(bin).chain(std::env::split_paths(&path))).unwrap();
+    }
+    let db = root.path().join("runs.db");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_verita"))
+        .env("PATH", &path)
+        .arg("--verus-repo")
+        .arg(&verus_repo)
+        .arg("--output-dir")
+        .arg(root.path().join("output"))
+        .args(["--label", "it's nightly", "--no-preflight", "--sqlite"])
+        .arg(&db)
+        .arg(&config)
+        .output()
+        .unwrap();
+    assert!(db.exists(), "{}", String::from_utf8_lossy(&output.stderr));
+    let query = |sql: &str| {
+        let output = std::process::Command::new("sqlite3")
+            .env("PATH", &path)
+            .arg(&db)
+            .arg(sql)
+            .output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(query("SELECT label FROM runs"), "it's nightly\n");
+    assert_eq!(
+        query("SELECT name || '|' || hash || '|' || status FROM projects ORDER BY name"),
+        format!("broken||infrastructure_error\nfixture|{hash}|success\nit's \"odd\"; --||infrastructure_error\n")
+    );
+}
+
+#[test]
+fn summary_json_is_written_as_projects_finish() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    // The second project's prepare script sees the summary as of the first's result
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "\n[[project]]\nname = \"second\"\ngit_url = \"{}\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\nprepare_script = \"cp {}/output/*/summary.json {}/partial.json\"\n",
+            project_repo.display(),
+            root.path().display(),
+            root.path().display()
+        ),
+    );
+    run_in(root.path(), &verus_repo, &config, &[]);
+    let partial: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.path().join("partial.json")).unwrap())
+            .unwrap();
+    assert_eq!(partial["complete"], false);
+    assert_eq!(partial["projects"].as_array().unwrap().len(), 1);
+    assert_eq!(partial["projects"][0]["name"], "fixture");
+}
+
+#[test]
+fn html_report_colors_failed_and_timed_out_rows() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let project = |name: &str, extra: &str| {
+        format!(
+            "\n[[project]]\nname = \"{name}\"\ngit_url = \"{}\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\n{extra}\n",
+            project_repo.display()
+        )
+    };
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &[
+            project(
+                "<failing>",
+                r#"prepare_script = "echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV""#,
+            ),
+            project(
+                "slow",
+                r#"timeout_seconds = 1
+prepare_script = "echo STUB_VERUS_SLEEP=60 > $VERITA_PREPARE_ENV""#,
+            ),
+        ]
+        .concat(),
+    );
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    let html = fs::read_to_string(summary.output_path.join("report.html")).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<style>"));
+    let row = |name: &str| {
+        html.lines()
+            .find(|l| l.contains(&format!("<td>{}</td>", name)))
+            .unwrap()
+            .to_string()
+    };
+    assert!(row("fixture").starts_with("<tr><td>"));
+    assert!(row("fixture").contains("<td>success</td><td class=\"number\">12</td>"));
+    assert!(row("fixture").contains("910 ms"));
+    assert!(row("&lt;failing&gt;").starts_with("<tr class=\"failed\">"));
+    assert!(row("slow").starts_with("<tr class=\"timed_out\">"));
+}
+
+#[test]
+fn prepare_script_can_set_verus_environment() {
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-single.json"),
+        r#"prepare_script = "printf '# flags\nRUSTFLAGS=-Copt-level=1\n' > $VERITA_PREPARE_ENV""#,
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(
+        output_json["runner"]["prepare_env"],
+        serde_json::json!(["RUSTFLAGS"])
+    );
+}
+
+#[test]
+fn report_rereads_output_dir() {
+    let (_root, summary, _hash) =
+        run_with_canned_output(include_str!("fixtures/verus-output-single.json"));
+    let reports = verita::report::load_output_dir(&summary.output_path).unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].status, "success");
+    assert_eq!(reports[0].verified, Some(12));
+    let table = verita::report::markdown(&reports, DurationUnit::Millis);
+    assert!(table.contains("| fixture | success | 12 | 0 | 100.0% |"));
+    let table = verita::report::markdown(&reports, DurationUnit::Seconds(3));
+    assert!(table.contains("| duration (s) | CPU time (s) |"));
+    assert!(table.ends_with("| 1.200 |\n"));
+
+    let summaries = verita::report::load_project_summaries(&summary.output_path).unwrap();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].project.name, "fixture");
+    assert_eq!(summaries[0].status, ProjectStatus::Success);
+    let html = verita::output::html_report("rerun", &summaries);
+    assert!(html.contains("<td>success</td><td class=\"number\">12</td>"));
+}
+
+#[test]
+fn compile_failure_is_distinct_from_verification_failure() {
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-single.json"),
+        r#"
+compile = true
+prepare_script = "echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV"
+"#,
+    );
+    assert_eq!(
+        summary.project_summaries[0].status,
+        ProjectStatus::CompileFailed
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["compile_success"], false);
+
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-per-crate.json"),
+        r#"
+compile = true
+prepare_script = "echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV"
+"#,
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Failure);
+}
+
+#[test]
+fn timeout_kills_verus_and_its_children() {
+    let start = std::time::Instant::now();
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-single.json"),
+        r#"
+timeout_seconds = 1
+retries = 2
+prepare_script = "echo STUB_VERUS_SLEEP=60 > $VERITA_PREPARE_ENV"
+"#,
+    );
+    // The stub's `sleep` holds the output pipes open, so this only returns early if it was
+    // killed along with the stub
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::TimedOut);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["timed_out"], true);
+    assert_eq!(output_json["runner"]["timeout_seconds"], 1);
+    assert_eq!(output_json["runner"]["retries"], 0);
+    assert!(
+        output_json["runner"]["verification_duration_ms"]
+            .as_u64()
+            .unwrap()
+            >= 1000
+    );
+
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-single.json"),
+        "timeout_seconds = 60\n",
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["timed_out"], false);
+}
+
+#[test]
+fn shallow_clone_fetches_an_older_commit() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, initial) = make_project_repo(root.path());
+    let repo = Repository::open(&project_repo).unwrap();
+    let sig = Signature::now("verita", "verita@example.com").unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        "second",
+        &parent.tree().unwrap(),
+        &[&parent],
+    )
+    .unwrap();
+    // libgit2's local transport ignores the depth, so this checks that the refspec is
+    // honored rather than that history was left out
+    let config = write_config(root.path(), &project_repo, "clone_depth = 1\n");
+    let text = fs::read_to_string(&config)
+        .unwrap()
+        .replace("refspec = \"main\"", &format!("refspec = \"{initial}\""));
+    fs::write(&config, text).unwrap();
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    assert_eq!(summary.project_summaries[0].hash, initial);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["shallow_clone"], true);
+}
+
+#[test]
+fn reclone_on_failure_reruns_projects_cloned_from_the_cache() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    // Fails once, as a bad checkout would
+    let flaky = root.path().join("flaky");
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "prepare_script = \"if [ -e {0} ]; then rm {0}; echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV; fi\"",
+            flaky.display()
+        ),
+    );
+    let run = |args: &[&str]| {
+        fs::write(&flaky, "").unwrap();
+        run_in(root.path(), &verus_repo, &config, args)
+    };
+
+    let summary = run(&["--reclone-on-failure", "--cache-dir", "{root}/cache"]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    let runner = &read_output_json(&summary, "fixture")["runner"];
+    assert_eq!(runner["recloned"], true);
+    assert_eq!(runner["cloned_from_cache"], false);
+    // Without the cache, the clone was already fresh
+    let summary = run(&["--reclone-on-failure"]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Failure);
+    assert_eq!(
+        read_output_json(&summary, "fixture")["runner"]["recloned"],
+        false
+    );
+}
+
+#[test]
+fn clone_cache_is_updated_and_repaired() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, initial) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let args = ["--cache-dir", "{root}/cache"];
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(summary.project_summaries[0].hash, initial);
+    assert_eq!(
+        read_output_json(&summary, "fixture")["runner"]["cloned_from_cache"],
+        true
+    );
+    let mirror = root
+        .path()
+        .join("cache")
+        .join(verita::clone_cache::cache_key(
+            &project_repo.display().to_string(),
+        ));
+    assert!(Repository::open_bare(&mirror).is_ok());
+
+    // A later commit is fetched into the cache
+    let repo = Repository::open(&project_repo).unwrap();
+    let sig = Signature::now("verita", "verita@example.com").unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let second = repo
+        .commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "second",
+            &parent.tree().unwrap(),
+            &[&parent],
+        )
+        .unwrap();
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(summary.project_summaries[0].hash, second.to_string());
+
+    // A broken cache is rebuilt
+    fs::remove_dir_all(&mirror).unwrap();
+    fs::create_dir_all(&mirror).unwrap();
+    fs::write(mirror.join("HEAD"), "garbage").unwrap();
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    assert_eq!(summary.project_summaries[0].hash, second.to_string());
+    assert!(Repository::open_bare(&mirror).is_ok());
+}
+
+#[test]
+fn selection_file_restricts_the_run() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    fs::write(
+        root.path().join("smoke.txt"),
+        "# smoke subset\nfixture  # fast\n",
+    )
+    .unwrap();
+
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &["--selection", "{root}/smoke.txt"],
+    );
+    assert_eq!(summary.project_summaries.len(), 1);
+}
+
+#[test]
+fn baseline_reports_moved_projects() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let baseline = |hash: &str| {
+        let json = serde_json::json!({"projects": [{"name": "fixture", "hash": hash}]});
+        fs::write(root.path().join("baseline.json"), json.to_string()).unwrap();
+    };
+    let args = ["--baseline", "{root}/baseline.json"];
+
+    baseline(&hash);
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(
+        summary.project_summaries[0].revision_change.as_deref(),
+        Some("unchanged")
+    );
+
+    assert_eq!(
+        read_output_json(&summary, "fixture")["runner"]["smt_changed"],
+        serde_json::Value::Null
+    );
+
+    baseline("0123456789abcdef");
+    let summary = run_in(root.path(), &verus_repo, &config, &args);
+    assert_eq!(
+        summary.project_summaries[0].revision_change,
+        Some(format!("0123456→{}", &hash[..7]))
+    );
+}
+
+#[test]
+fn allowed_failures_are_reported_separately() {
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-per-crate.json"),
+        r#"
+allow_failure = true
+prepare_script = "echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV"
+"#,
+    );
+    let project = &summary.project_summaries[0];
+    assert_eq!(project.status, ProjectStatus::AllowedFailure);
+    assert_eq!(
+        project.explanation(),
+        "allowed failure: 2 of 14 functions failed to verify"
+    );
+}
+
+#[test]
+fn only_failures_that_are_not_allowed_fail_the_process() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-per-crate.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let exit_code = |extra: &str| {
+        let config = write_config(
+            root.path(),
+            &project_repo,
+            &format!(
+                "{}\nprepare_script = \"echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV\"",
+                extra
+            ),
+        );
+        std::process::Command::new(env!("CARGO_BIN_EXE_verita"))
+            .arg("--verus-repo")
+            .arg(&verus_repo)
+            .arg("--output-dir")
+            .arg(root.path().join("output"))
+            .args(["--label", "test", "--no-preflight", "--quiet"])
+            .arg(&config)
+            .status()
+            .unwrap()
+            .code()
+    };
+    assert_eq!(exit_code(""), Some(1));
+    assert_eq!(exit_code("allow_failure = true"), Some(0));
+}
+
+#[test]
+fn failures_are_retried() {
+    let (_root, summary, _hash) = run_with_project_config(
+        include_str!("fixtures/verus-output-per-crate.json"),
+        r#"
+retries = 2
+prepare_script = "echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV"
+"#,
+    );
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Failure);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["retries"], 2);
+}
+
+#[test]
+fn sparse_checkout_skips_other_paths() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let repo = Repository::open(&project_repo).unwrap();
+    fs::create_dir_all(project_repo.join("docs")).unwrap();
+    fs::write(project_repo.join("docs/notes.txt"), "not needed\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("docs/notes.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("verita", "verita@example.com").unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "docs", &tree, &[&parent])
+        .unwrap();
+    // Verus "fails" if the clone has more than it should
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        r#"sparse_paths = ["src"]
+prepare_script = "test -e Cargo.toml && test ! -e docs || echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV""#,
+    );
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["sparse_checkout"], true);
+}
+
+#[test]
+fn profile_is_saved_in_folded_stack_format() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-time-expanded.json"),
+        "",
+        &["--profile-verus"],
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["smt_function_count"], 3);
+    let profile = summary
+        .output_path
+        .join("fixture/artifacts/verus-profile.folded");
+    assert_eq!(
+        output_json["runner"]["verus_profile"],
+        profile.to_str().unwrap()
+    );
+    assert_eq!(
+        fs::read_to_string(profile).unwrap(),
+        "fixture::a;fixture::a::f 500\nfixture::a;fixture::a::g 200\nfixture::b;fixture::b::h 200\n"
+    );
+}
+
+#[test]
+fn only_failed_reruns_failures_from_an_earlier_run() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let previous = root.path().join("previous");
+    fs::create_dir_all(&previous).unwrap();
+    let json = serde_json::json!({
+        "runner": {"status": "failure", "run_configuration": {"name": "fixture"}}
+    });
+    fs::write(previous.join("fixture.json"), json.to_string()).unwrap();
+
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &["--only-failed", "{root}/previous"],
+    );
+    assert_eq!(summary.project_summaries.len(), 1);
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &[
+            "--only-failed",
+            "{root}/previous",
+            "--only-status",
+            "compile_failed",
+        ],
+    );
+    assert!(summary.project_summaries.is_empty());
+
+    let json = serde_json::json!({
+        "runner": {"status": "timed_out", "run_configuration": {"name": "fixture"}}
+    });
+    fs::write(previous.join("fixture.json"), json.to_string()).unwrap();
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &["--only-failed", "{root}/previous"],
+    );
+    assert_eq!(summary.project_summaries.len(), 1);
+}
+
+#[test]
+fn vir_errors_can_be_reported_as_infrastructure_errors() {
+    let canned = include_str!("fixtures/verus-output-single.json")
+        .replace(
+            "\"encountered-vir-error\": false",
+            "\"encountered-vir-error\": true",
+        )
+        .replace("\"success\": true", "\"success\": false");
+    let failing = r#"prepare_script = "echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV""#;
+    let (_root, summary, _hash) = run_with_args(&canned, failing, &[]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Failure);
+    let (root, summary, _hash) = run_with_args(
+        &canned,
+        failing,
+        &[
+            "--vir-errors-as-infra",
+            "--summary-json",
+            "{root}/summary.json",
+        ],
+    );
+    assert_eq!(
+        summary.project_summaries[0].status,
+        ProjectStatus::InfrastructureError
+    );
+    assert_eq!(summary.infrastructure_error_count(), 1);
+    let summary_json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(root.path().join("summary.json")).unwrap())
+            .unwrap();
+    assert_eq!(summary_json["counts"]["infrastructure_error"], 1);
+    assert_eq!(summary_json["vir_errors"], 1);
+}
+
+#[test]
+fn extra_args_file_is_appended_to_extra_args() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    fs::write(
+        root.path().join("fixture.args"),
+        "# heavyweight flags\n--rlimit\n\n30\n",
+    )
+    .unwrap();
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        "extra_args = [\"--expand-errors\"]\nextra_args_file = \"fixture.args\"",
+    );
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    let output_json = read_output_json(&summary, "fixture");
+    let verus_args = output_json["runner"]["verus_args"].as_array().unwrap();
+    assert!(verus_args.ends_with(&["--expand-errors".into(), "--rlimit".into(), "30".into()]));
+    assert_eq!(
+        output_json["runner"]["run_configuration"]["extra_args_file"],
+        root.path().join("fixture.args").to_str().unwrap()
+    );
+}
+
+#[test]
+fn dry_run_plans_without_running() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        r#"extra_args = ["--expand-errors"]
+prepare_script = "touch {root}/prepared""#
+            .replace("{root}", &root.path().display().to_string())
+            .as_str(),
+    );
+    let output_dir = root.path().join("output");
+    let args = |config: &Path| {
+        Args::try_parse_from([
+            "verita".as_ref(),
+            "--verus-repo".as_ref(),
+            verus_repo.as_os_str(),
+            "--output-dir".as_ref(),
+            output_dir.as_os_str(),
+            "--label".as_ref(),
+            "test".as_ref(),
+            "--dry-run".as_ref(),
+            config.as_os_str(),
+        ])
+        .unwrap()
+    };
+    let plan = verita::dry_run(&args(&config)).unwrap();
+    assert_eq!(plan.len(), 1);
+    assert_eq!(plan[0].refspec_problem, None);
+    let command = &plan[0].verus_command;
+    assert!(command.contains("target-verus/release/verus --output-json --time src/lib.rs"));
+    assert!(command.ends_with(" --expand-errors"));
+    assert!(!output_dir.exists());
+    assert!(!root.path().join("prepared").exists());
+
+    let text = fs::read_to_string(&config)
+        .unwrap()
+        .replace("refspec = \"main\"", "refspec = \"no-such-branch\"");
+    fs::write(&config, text).unwrap();
+    let plan = verita::dry_run(&args(&config)).unwrap();
+    assert!(plan[0]
+        .refspec_problem
+        .as_ref()
+        .unwrap()
+        .contains("no branch or tag no-such-branch"));
+}
+
+#[test]
+fn doctor_reports_each_check() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let output_dir = root.path().join("output");
+    let args = Args::try_parse_from([
+        "verita".as_ref(),
+        "--verus-repo".as_ref(),
+        verus_repo.as_os_str(),
+        "--output-dir".as_ref(),
+        output_dir.as_os_str(),
+        "--label".as_ref(),
+        "test".as_ref(),
+        config.as_os_str(),
+    ])
+    .unwrap();
+    let checks = verita::doctor::run_checks(&args);
+    for c in checks.iter() {
+        println!("{}", c);
+    }
+    let status = |name: &str| checks.iter().find(|c| c.name == name).unwrap().status;
+    use verita::doctor::CheckStatus;
+    assert_eq!(status("verus repository"), CheckStatus::Pass);
+    assert_eq!(status("verus binary"), CheckStatus::Pass);
+    assert_eq!(status("configuration"), CheckStatus::Pass);
+    assert_eq!(status("output directory"), CheckStatus::Pass);
+    assert_eq!(status("access to fixture"), CheckStatus::Pass);
+    // The fake Verus checkout has no solvers
+    assert_eq!(status("z3"), CheckStatus::Fail);
+}
+
+#[test]
+fn sqlite3_is_checked_before_anything_runs() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    // A PATH with nothing but, optionally, a fake sqlite3
+    let bin = root.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let verita = |subcommand: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_verita"))
+            .env("PATH", &bin)
+            .args(subcommand)
+            .arg("--verus-repo")
+            .arg(&verus_repo)
+            .arg("--output-dir")
+            .arg(root.path().join("output"))
+            .args(["--label", "test", "--no-preflight", "--sqlite"])
+            .arg(root.path().join("runs.db"))
+            .arg(&config)
+            .output()
+            .unwrap()
+    };
+
+    let output = verita(&[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot run sqlite3"));
+    assert!(!root.path().join("output").exists());
+    let output = verita(&["doctor"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("FAIL: sqlite3: cannot run sqlite3"));
+
+    let sqlite3 = bin.join("sqlite3");
+    fs::write(&sqlite3, "#!/bin/sh\necho '3.45.1 2024-01-30 16:01:20'\n").unwrap();
+    fs::set_permissions(&sqlite3, fs::Permissions::from_mode(0o755)).unwrap();
+    let output = verita(&["doctor"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("pass: sqlite3: version 3.45.1"));
+}
+
+#[test]
+fn capture_smt_logs_queries_to_the_artifacts_directory() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &["--capture-smt"],
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    let verus_args = output_json["runner"]["verus_args"].as_array().unwrap();
+    let smt_dir = std::path::absolute(summary.output_path.join("fixture/artifacts/smt")).unwrap();
+    assert!(verus_args.ends_with(&[
+        "--log".into(),
+        "smt".into(),
+        "--log-dir".into(),
+        smt_dir.to_str().unwrap().into()
+    ]));
+    // The stub Verus writes no queries
+    assert_eq!(output_json["runner"]["smt_hash"], serde_json::Value::Null);
+}
+
+#[test]
+fn seeds_with_capture_smt_vary_only_the_seed_and_log_directory() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    // Record each invocation's arguments, one line per run
+    let release = verus_repo.join("source/target-verus/release");
+    let invocations = root.path().join("invocations");
+    fs::write(
+        release.join("verus"),
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\ncat '{}'\n",
+            invocations.display(),
+            release.join("canned-output.json").display()
+        ),
+    )
+    .unwrap();
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &["--seeds", "2", "--capture-smt"],
+    );
+    let smt_dir = std::path::absolute(summary.output_path.join("fixture/artifacts")).unwrap();
+    let invocations = fs::read_to_string(&invocations).unwrap();
+    let invocations: Vec<_> = invocations.lines().collect();
+    assert_eq!(invocations.len(), 2);
+    assert!(invocations[0].ends_with(&format!(
+        "--smt-option smt.random_seed=0 --log smt --log-dir {}/smt",
+        smt_dir.display()
+    )));
+    assert!(invocations[1].ends_with(&format!(
+        "--smt-option smt.random_seed=1 --log smt --log-dir {}/smt-seed-1",
+        smt_dir.display()
+    )));
+    assert!(!invocations[1].contains("random_seed=0"));
+}
+
+/// A container runtime that runs the command on the host, recording each invocation's image
+/// in `images.log` and each container it is asked to kill in `kills.log`, and a configuration
+/// that uses it for a project with `extra` settings
+fn write_container_config(root: &Path, project_repo: &Path, extra: &str) -> PathBuf {
+    let runtime = root.join("fake-runtime");
+    fs::write(
+        &runtime,
+        format!(
+            r#"#!/bin/sh
+if [ "$1" = kill ]; then
+    echo "$2" >> '{}'
+    exit 0
+fi
+shift 2
+while true; do
+    case "$1" in
+        -v|--name|--user) shift 2 ;;
+        -w) cd "$2"; shift 2 ;;
+        -e) export "$2"; shift 2 ;;
+        *) break ;;
+    esac
+done
+echo "$1" >> '{}'
+shift
+exec "$@"
+"#,
+            root.join("kills.log").display(),
+            root.join("images.log").display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&runtime, fs::Permissions::from_mode(0o755)).unwrap();
+    let config = write_config(root, project_repo, extra);
+    let text = fs::read_to_string(&config).unwrap().replacen(
+        "[[project]]",
+        &format!(
+            "container = \"verus-ci\"\ncontainer_runtime = \"{}\"\n\n[[project]]",
+            runtime.display()
+        ),
+        1,
+    );
+    fs::write(&config, text).unwrap();
+    config
+}
+
+#[test]
+fn container_runs_prepare_and_verus_through_the_runtime() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_container_config(root.path(), &project_repo, r#"prepare_script = "true""#);
+
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::Success);
+    assert_eq!(
+        fs::read_to_string(root.path().join("images.log")).unwrap(),
+        "verus-ci\nverus-ci\n"
+    );
+    assert!(!root.path().join("kills.log").exists());
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["container"], "verus-ci");
+}
+
+#[test]
+fn timeout_kills_the_container() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_container_config(
+        root.path(),
+        &project_repo,
+        "timeout_seconds = 1\nprepare_script = \"echo STUB_VERUS_SLEEP=60 > $VERITA_PREPARE_ENV\"",
+    );
+
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    assert_eq!(summary.project_summaries[0].status, ProjectStatus::TimedOut);
+    assert_eq!(
+        fs::read_to_string(root.path().join("kills.log")).unwrap(),
+        format!("{}\n", verita::container::Container::name("fixture"))
+    );
+}
+
+#[test]
+fn unexpected_solver_versions_fail_the_run() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let z3 = verus_repo.join("source/z3");
+    fs::write(&z3, "#!/bin/sh\necho 'Z3 version 4.12.5 - 64 bit'\n").unwrap();
+    fs::set_permissions(&z3, fs::Permissions::from_mode(0o755)).unwrap();
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let expect = |version: &str| {
+        let text = fs::read_to_string(&config).unwrap();
+        let text = text.replacen(
+            "[[project]]",
+            &format!("expected_z3_version = \"{version}\"\n\n[[project]]"),
+            1,
+        );
+        fs::write(root.path().join("pinned.toml"), text).unwrap();
+        root.path().join("pinned.toml")
+    };
+    let output_dir = root.path().join("output");
+    let run = |config: &Path, lenient: bool| {
+        let mut argv: Vec<&OsStr> = vec![
+            "verita".as_ref(),
+            "--verus-repo".as_ref(),
+            verus_repo.as_os_str(),
+            "--output-dir".as_ref(),
+            output_dir.as_os_str(),
+            "--label".as_ref(),
+            "test".as_ref(),
+            "--no-preflight".as_ref(),
+        ];
+        if lenient {
+            argv.push("--lenient-solver-versions".as_ref());
+        }
+        argv.push(config.as_os_str());
+        verita::run(&Args::try_parse_from(argv).unwrap())
+    };
+
+    assert!(run(&expect("4.12"), false).is_ok());
+    let err = run(&expect("4.13"), false).err().unwrap();
+    assert!(err
+        .to_string()
+        .contains("expected z3 version 4.13, but found 4.12.5"));
+    assert!(run(&expect("4.13"), true).is_ok());
+}
+
+#[test]
+fn output_json_can_be_redacted() {
+    let (_root, summary, _hash) = run_with_args(
+        include_str!("fixtures/verus-output-single.json"),
+        "",
+        &[
+            "--redact",
+            "runner.stderr",
+            "--redact",
+            "runner.run_configuration.git_url",
+        ],
+    );
+    let output_json = read_output_json(&summary, "fixture");
+    assert!(output_json["runner"].get("stderr").is_none());
+    assert!(output_json["runner"]["run_configuration"]
+        .get("git_url")
+        .is_none());
+    assert_eq!(
+        output_json["runner"]["run_configuration"]["name"],
+        "fixture"
+    );
+}
+
+#[test]
+fn output_transform_sees_the_assembled_json() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(root.path(), &project_repo, "");
+    let output_dir = root.path().join("output");
+    let args = Args::try_parse_from([
+        "verita".as_ref(),
+        "--verus-repo".as_ref(),
+        verus_repo.as_os_str(),
+        "--output-dir".as_ref(),
+        output_dir.as_os_str(),
+        "--label".as_ref(),
+        "test".as_ref(),
+        "--no-preflight".as_ref(),
+        "--redact".as_ref(),
+        "team".as_ref(),
+        config.as_os_str(),
+    ])
+    .unwrap();
+    let summary = verita::run_with_output_transform(
+        &args,
+        Some(Box::new(|project, json| {
+            json["team"] = "verification".into();
+            json["runner_status"] = json["runner"]["status"].clone();
+            json["project_name"] = project.name.clone().into();
+        })),
+    )
+    .unwrap();
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner_status"], "success");
+    assert_eq!(output_json["project_name"], "fixture");
+    // --redact applies after the transform
+    assert!(output_json.get("team").is_none());
+}
+
+#[test]
+fn jobs_run_projects_concurrently_and_report_in_order() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    // Each project's prepare script waits (briefly) for the other's to start, and makes
+    // Verus fail if it never does
+    let prepare = |me: &str, other: &str| {
+        format!(
+            r#"prepare_script = "touch {root}/{me}; for i in $(seq 50); do test -e {root}/{other} && exit; sleep 0.1; done; echo STUB_VERUS_EXIT=1 > $VERITA_PREPARE_ENV""#,
+            root = root.path().display()
+        )
+    };
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "{}\n\n[[project]]\nname = \"second\"\ngit_url = \"{}\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\n{}\n",
+            prepare("fixture", "second"),
+            project_repo.display(),
+            prepare("second", "fixture")
+        ),
+    );
+    let summary = run_in(root.path(), &verus_repo, &config, &["--jobs", "2"]);
+    let results: Vec<_> = summary
+        .project_summaries
+        .iter()
+        .map(|p| (p.project.name.as_str(), p.status))
+        .collect();
+    assert_eq!(
+        results,
+        [
+            ("fixture", ProjectStatus::Success),
+            ("second", ProjectStatus::Success)
+        ]
+    );
+}
+
+#[test]
+fn project_and_filter_select_by_name() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "\n[[project]]\nname = \"second\"\ngit_url = \"{}\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\n",
+            project_repo.display()
+        ),
+    );
+    let output_dir = root.path().join("output");
+    let plan = |selector: &[&str]| {
+        let mut argv: Vec<&OsStr> = vec![
+            "verita".as_ref(),
+            "--verus-repo".as_ref(),
+            verus_repo.as_os_str(),
+            "--output-dir".as_ref(),
+            output_dir.as_os_str(),
+            "--label".as_ref(),
+            "test".as_ref(),
+            "--error-on-empty".as_ref(),
+        ];
+        argv.extend(selector.iter().map(OsStr::new));
+        argv.push(config.as_os_str());
+        verita::dry_run(&Args::try_parse_from(argv).unwrap())
+            .map(|plan| plan.into_iter().map(|p| p.name).collect::<Vec<_>>())
+    };
+    assert_eq!(plan(&["--project", "second"]).unwrap(), ["second"]);
+    assert_eq!(
+        plan(&["--project", "second", "--project", "fixture"]).unwrap(),
+        ["fixture", "second"]
+    );
+    assert_eq!(plan(&["--filter", "^fix"]).unwrap(), ["fixture"]);
+    let err = plan(&["--project", "third"]).unwrap_err().to_string();
+    assert!(err.contains("unknown project third; the projects are: fixture, second"));
+    let err = plan(&["--project", "second", "--filter", "^fix"])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("(the projects are: fixture, second)"));
+}
+
+#[test]
+fn clone_failures_are_retried_and_recorded() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    // Nothing listens on port 1, so connecting fails with a network error
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        "\n[[project]]\nname = \"dead\"\ngit_url = \"http://127.0.0.1:1/dead.git\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\n",
+    );
+    let start = std::time::Instant::now();
+    let summary = run_in(root.path(), &verus_repo, &config, &["--clone-retries", "1"]);
+    assert!(start.elapsed() >= std::time::Duration::from_secs(1));
+    let statuses: Vec<_> = summary
+        .project_summaries
+        .iter()
+        .map(|p| (p.project.name.as_str(), p.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("fixture", ProjectStatus::Success),
+            ("dead", ProjectStatus::InfrastructureError)
+        ]
+    );
+    assert_eq!(summary.infrastructure_error_count(), 1);
+    let output_json = read_output_json(&summary, "dead");
+    assert_eq!(output_json["runner"]["failed_stage"], "clone");
+    assert!(output_json["runner"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("127.0.0.1"));
+
+    // A refspec that doesn't resolve is not retried
+    let text = fs::read_to_string(&config).unwrap().replacen(
+        "\nrefspec = \"main\"",
+        "\nrefspec = \"no-such-branch\"",
+        1,
+    );
+    fs::write(&config, text).unwrap();
+    let start = std::time::Instant::now();
+    let summary = run_in(
+        root.path(),
+        &verus_repo,
+        &config,
+        &["--clone-retries", "3", "--project", "fixture"],
+    );
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    let output_json = read_output_json(&summary, "fixture");
+    assert!(output_json["runner"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("failed to find no-such-branch"));
+}
+
+#[test]
+fn a_failing_project_does_not_stop_the_others() {
+    let root = TempDir::new("verita-test").unwrap();
+    let verus_repo = make_verus_repo(
+        root.path(),
+        include_str!("fixtures/verus-output-single.json"),
+    );
+    let (project_repo, _hash) = make_project_repo(root.path());
+    let config = write_config(
+        root.path(),
+        &project_repo,
+        &format!(
+            "workdir = \"missing\"\n\n[[project]]\nname = \"second\"\ngit_url = \"{}\"\nrefspec = \"main\"\ncrate_root = \"src/lib.rs\"\n",
+            project_repo.display()
+        ),
+    );
+    let summary = run_in(root.path(), &verus_repo, &config, &[]);
+    let statuses: Vec<_> = summary
+        .project_summaries
+        .iter()
+        .map(|p| (p.project.name.as_str(), p.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("fixture", ProjectStatus::InfrastructureError),
+            ("second", ProjectStatus::Success)
+        ]
+    );
+    assert_eq!(summary.infrastructure_error_count(), 1);
+    let output_json = read_output_json(&summary, "fixture");
+    assert_eq!(output_json["runner"]["failed_stage"], "run");
+    assert!(output_json["runner"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("workdir"));
+}
@@ -0,0 +1,35 @@
+use verita::parse_solver_version;
+
+#[test]
+fn solver_version_with_invalid_utf8() {
+    let mut banner = b"Z3 version 4.12.5 - 64 bit\n".to_vec();
+    banner.extend_from_slice(&[0xff, 0xfe, 0x00, 0xc3]);
+    assert_eq!(
+        parse_solver_version(&banner, "z3", "Z3 version").unwrap(),
+        "4.12.5"
+    );
+    assert!(parse_solver_version(&[0xff, 0xfe], "z3", "Z3 version").is_err());
+}
+
+#[test]
+fn solver_oom_signatures() {
+    assert!(verita::looks_like_solver_oom(
+        b"error: Z3 reported: (error \"out of memory\")\n"
+    ));
+    assert!(verita::looks_like_solver_oom(
+        b"terminate called after throwing an instance of 'std::bad_alloc'"
+    ));
+    assert!(!verita::looks_like_solver_oom(
+        b"error: assertion failed\n  --> src/lib.rs:3:5"
+    ));
+}
+
+#[test]
+fn solver_versions_match_by_component_prefix() {
+    use verita::solver_version_matches;
+    assert!(solver_version_matches("4.12", "4.12.5"));
+    assert!(solver_version_matches("4.12.5", "4.12.5"));
+    assert!(!solver_version_matches("4.12", "4.120.1"));
+    assert!(!solver_version_matches("4.12", "4.13.0"));
+    assert!(!solver_version_matches("4.12", "unknown"));
+}